@@ -0,0 +1,156 @@
+//! Recorded answers, used to spot regressions when old days are re-run.
+//!
+//! The answers file is TOML with one table per day:
+//!
+//! ```toml
+//! [day_3]
+//! part1 = 4361
+//! part2 = 467835
+//! ```
+//!
+//! Values may be integers or strings; they are compared with the printed answer.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use toml::{Table, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected } => write!(f, "FAIL, expected {}", expected),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Toml(e) => write!(f, "{}", e),
+            AnswersError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+#[derive(Debug, Default)]
+pub struct Answers {
+    expected: HashMap<(u8, u8), String>,
+}
+
+impl Answers {
+    pub fn from_toml(text: &str) -> Result<Answers, AnswersError> {
+        let table: Table = text.parse().map_err(AnswersError::Toml)?;
+        let mut answers = Answers::default();
+
+        for (day_key, parts) in table {
+            let day = day_key
+                .strip_prefix("day_")
+                .and_then(|d| d.parse::<u8>().ok())
+                .ok_or_else(|| {
+                    AnswersError::Invalid(format!(
+                        "expected a table like [day_3], found [{}]",
+                        day_key
+                    ))
+                })?;
+            let Value::Table(parts) = parts else {
+                return Err(AnswersError::Invalid(format!(
+                    "[{}] should be a table",
+                    day_key
+                )));
+            };
+
+            for (part_key, value) in parts {
+                let part = match part_key.as_str() {
+                    "part1" => 1,
+                    "part2" => 2,
+                    other => {
+                        return Err(AnswersError::Invalid(format!(
+                            "expected part1 or part2 in [{}], found {}",
+                            day_key, other
+                        )))
+                    }
+                };
+                let value = match value {
+                    Value::String(s) => s,
+                    Value::Integer(i) => i.to_string(),
+                    other => {
+                        return Err(AnswersError::Invalid(format!(
+                            "{}.{} should be a string or an integer, found {}",
+                            day_key, part_key, other
+                        )))
+                    }
+                };
+                answers.expected.insert((day, part), value);
+            }
+        }
+
+        Ok(answers)
+    }
+
+    pub fn expected(&self, day: u8, part: u8) -> Option<&str> {
+        self.expected.get(&(day, part)).map(|s| s.as_str())
+    }
+
+    pub fn check(&self, day: u8, part: u8, actual: &str) -> Verdict {
+        match self.expected(day, part) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == actual => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gen_answers() -> Answers {
+        Answers::from_toml(
+            "\
+[day_3]
+part1 = 4361
+part2 = \"467835\"
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn check_should_report_each_verdict() {
+        let answers = gen_answers();
+        assert_eq!(answers.check(3, 1, "4361"), Verdict::Pass);
+        assert_eq!(
+            answers.check(3, 2, "1"),
+            Verdict::Fail {
+                expected: "467835".to_string()
+            }
+        );
+        assert_eq!(answers.check(4, 1, "13"), Verdict::Unknown);
+    }
+
+    #[test]
+    fn from_toml_should_reject_unknown_keys() {
+        assert!(Answers::from_toml("[day_three]\npart1 = 1").is_err());
+        assert!(Answers::from_toml("[day_3]\npart3 = 1").is_err());
+        assert!(Answers::from_toml("[day_3]\npart1 = 1.5").is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Timing for the parse phase and each part of a day, over repeated runs.
+
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::error::ParseError;
+use crate::solution::Day;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        assert!(!samples.is_empty(), "at least one sample is needed");
+        samples.sort();
+        Stats {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayTimings {
+    pub day: u8,
+    pub runs: usize,
+    pub phases: Vec<(Phase, Stats)>,
+}
+
+/// Parses `input` and answers both parts `runs` times, timing each phase separately.
+pub fn bench_day(day: &Day, input: &str, runs: usize) -> Result<DayTimings, ParseError> {
+    let runs = runs.max(1);
+    let mut parse = Vec::with_capacity(runs);
+    let mut part1 = Vec::with_capacity(runs);
+    let mut part2 = Vec::with_capacity(runs);
+
+    for _ in 0..runs {
+        let start = Instant::now();
+        let solution = (day.parse)(black_box(input))?;
+        parse.push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(solution.part1());
+        part1.push(start.elapsed());
+
+        let start = Instant::now();
+        black_box(solution.part2());
+        part2.push(start.elapsed());
+    }
+
+    Ok(DayTimings {
+        day: day.day,
+        runs,
+        phases: vec![
+            (Phase::Parse, Stats::from_samples(parse)),
+            (Phase::Part1, Stats::from_samples(part1)),
+            (Phase::Part2, Stats::from_samples(part2)),
+        ],
+    })
+}
+
+pub fn to_table(timings: &[DayTimings]) -> String {
+    let mut out = format!(
+        "{:>3}  {:<6} {:>5}  {:>12} {:>12} {:>12}\n",
+        "day", "phase", "runs", "min", "median", "max"
+    );
+    for day in timings {
+        for (phase, stats) in &day.phases {
+            let _ = writeln!(
+                out,
+                "{:>3}  {:<6} {:>5}  {:>12} {:>12} {:>12}",
+                day.day,
+                phase.name(),
+                day.runs,
+                format!("{:.2?}", stats.min),
+                format!("{:.2?}", stats.median),
+                format!("{:.2?}", stats.max)
+            );
+        }
+    }
+    out
+}
+
+pub fn to_csv(timings: &[DayTimings]) -> String {
+    let mut out = String::from("day,phase,runs,min_ns,median_ns,max_ns\n");
+    for day in timings {
+        for (phase, stats) in &day.phases {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                day.day,
+                phase.name(),
+                day.runs,
+                stats.min.as_nanos(),
+                stats.median.as_nanos(),
+                stats.max.as_nanos()
+            );
+        }
+    }
+    out
+}
+
+pub fn to_json(timings: &[DayTimings]) -> String {
+    let rows: Vec<String> = timings
+        .iter()
+        .flat_map(|day| {
+            day.phases.iter().map(move |(phase, stats)| {
+                format!(
+                    "  {{\"day\": {}, \"phase\": \"{}\", \"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+                    day.day,
+                    phase.name(),
+                    day.runs,
+                    stats.min.as_nanos(),
+                    stats.median.as_nanos(),
+                    stats.max.as_nanos()
+                )
+            })
+        })
+        .collect();
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats_should_sort_samples() {
+        let stats = Stats::from_samples(vec![ms(5), ms(1), ms(9), ms(3), ms(4)]);
+        assert_eq!(
+            stats,
+            Stats {
+                min: ms(1),
+                median: ms(4),
+                max: ms(9)
+            }
+        );
+    }
+
+    #[test]
+    fn bench_day_should_time_every_phase() {
+        let day = days::get(4).unwrap();
+        let timings = bench_day(day, "Card 1: 41 48 | 83 41\nCard 2: 1 | 2", 3).unwrap();
+        assert_eq!(timings.runs, 3);
+        let phases: Vec<Phase> = timings.phases.iter().map(|(p, _)| *p).collect();
+        assert_eq!(phases, vec![Phase::Parse, Phase::Part1, Phase::Part2]);
+    }
+
+    #[test]
+    fn csv_should_have_a_row_per_phase() {
+        let stats = Stats::from_samples(vec![ms(2)]);
+        let timings = vec![DayTimings {
+            day: 1,
+            runs: 1,
+            phases: vec![(Phase::Parse, stats), (Phase::Part1, stats)],
+        }];
+        assert_eq!(
+            to_csv(&timings),
+            "day,phase,runs,min_ns,median_ns,max_ns\n1,parse,1,2000000,2000000,2000000\n1,part1,1,2000000,2000000,2000000\n"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Local settings, read from `advent_of_code.toml` in the working directory:
+//!
+//! ```toml
+//! inputs_dir = "inputs"
+//! session = "53616c7465645f5f..."
+//! base_url = "https://adventofcode.com/2023"
+//! ```
+//!
+//! The `AOC_INPUTS_DIR` environment variable takes precedence over the file.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use toml::{Table, Value};
+
+pub const CONFIG_FILE: &str = "advent_of_code.toml";
+pub const INPUTS_DIR_VAR: &str = "AOC_INPUTS_DIR";
+pub const DEFAULT_INPUTS_DIR: &str = "src/input_files";
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2023";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub inputs_dir: PathBuf,
+    /// The adventofcode.com session cookie, needed to download inputs.
+    pub session: Option<String>,
+    pub base_url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            inputs_dir: PathBuf::from(DEFAULT_INPUTS_DIR),
+            session: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "could not read {}: {}", CONFIG_FILE, e),
+            ConfigError::Toml(e) => write!(f, "could not parse {}: {}", CONFIG_FILE, e),
+            ConfigError::Invalid(message) => write!(f, "{}: {}", CONFIG_FILE, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Reads the config file if there is one, then applies environment overrides.
+    pub fn load() -> Result<Config, ConfigError> {
+        let text = match fs::read_to_string(CONFIG_FILE) {
+            Ok(text) => Some(text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(ConfigError::Io(e)),
+        };
+        Config::resolve(
+            text.as_deref(),
+            std::env::var_os(INPUTS_DIR_VAR).map(PathBuf::from),
+        )
+    }
+
+    fn resolve(file: Option<&str>, inputs_dir_var: Option<PathBuf>) -> Result<Config, ConfigError> {
+        let mut config = match file {
+            Some(text) => Config::from_toml(text)?,
+            None => Config::default(),
+        };
+        if let Some(dir) = inputs_dir_var {
+            config.inputs_dir = dir;
+        }
+        Ok(config)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Config, ConfigError> {
+        let table: Table = text.parse().map_err(ConfigError::Toml)?;
+        let mut config = Config::default();
+
+        for (key, value) in table {
+            let Value::String(value) = value else {
+                return Err(ConfigError::Invalid(format!(
+                    "{} should be a string, found {}",
+                    key, value
+                )));
+            };
+            match key.as_str() {
+                "inputs_dir" => config.inputs_dir = PathBuf::from(value),
+                "session" => config.session = Some(value),
+                "base_url" => config.base_url = value,
+                other => return Err(ConfigError::Invalid(format!("unknown setting {}", other))),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_var_should_override_file() {
+        let file = Some("inputs_dir = \"from_file\"");
+        assert_eq!(
+            Config::resolve(file, None).unwrap().inputs_dir,
+            PathBuf::from("from_file")
+        );
+        assert_eq!(
+            Config::resolve(file, Some(PathBuf::from("from_env")))
+                .unwrap()
+                .inputs_dir,
+            PathBuf::from("from_env")
+        );
+        assert_eq!(Config::resolve(None, None).unwrap(), Config::default());
+    }
+
+    #[test]
+    fn from_toml_should_read_fetch_settings() {
+        let config =
+            Config::from_toml("session = \"abc\"\nbase_url = \"http://localhost:8080\"").unwrap();
+        assert_eq!(config.session.as_deref(), Some("abc"));
+        assert_eq!(config.base_url, "http://localhost:8080");
+        assert_eq!(config.inputs_dir, PathBuf::from(DEFAULT_INPUTS_DIR));
+    }
+
+    #[test]
+    fn from_toml_should_reject_unknown_settings() {
+        assert!(Config::from_toml("input_dir = \"typo\"").is_err());
+        assert!(Config::from_toml("inputs_dir = 3").is_err());
+    }
+}
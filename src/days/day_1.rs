@@ -1,47 +1,369 @@
-pub trait StringHelpers {
-    fn get_first_last_number(&self) -> u32;
-    fn replace_string_numbers(&self) -> String;
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+use crate::error::ParseError;
+use crate::scanner::{Automaton, PatternMatch};
+use crate::solution::{Answer, Example, Solution};
+use crate::utilities::json_string;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Digit,
+    Word,
 }
 
-impl StringHelpers for str {
-    fn get_first_last_number(&self) -> u32 {
-        let string_with_digits = self.replace_string_numbers();
-        let digits: Vec<char> = string_with_digits.chars().filter(|c| c.is_digit(10)).collect();
-        match digits.len() {
-            0 => 0,
-            1 => format!("{}{}", digits[0], digits[0]).parse().unwrap(),
-            _ => format!("{}{}", digits[0], digits[digits.len() - 1]).parse().unwrap(),
+impl TokenKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenKind::Digit => "digit",
+            TokenKind::Word => "word",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    text: String,
+    value: u32,
+    kind: TokenKind,
+}
+
+pub const ENGLISH_WORDS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+/// The spellings of each digit that count when reading a calibration line.
+#[derive(Debug, Clone, Default)]
+pub struct Vocabulary {
+    tokens: Vec<Token>,
+}
+
+impl Vocabulary {
+    /// The digit characters 0 to 9.
+    pub fn digits() -> Vocabulary {
+        Vocabulary::default().with_digits()
+    }
+
+    /// Digits plus the English words one to nine.
+    pub fn english() -> Vocabulary {
+        Vocabulary::digits().with_words(&ENGLISH_WORDS)
+    }
+
+    pub fn with_digits(mut self) -> Vocabulary {
+        for value in 0..=9 {
+            self.tokens.push(Token {
+                text: value.to_string(),
+                value,
+                kind: TokenKind::Digit,
+            });
         }
+        self
     }
 
-    fn replace_string_numbers(&self) -> String {
-        let number_strings = vec![
-            ("one", "o1ne"),
-            ("two", "t2wo"),
-            ("three", "thr3ee"),
-            ("four", "f4our"),
-            ("five", "f5ive"),
-            ("six", "s6ix"),
-            ("seven", "se7ven"),
-            ("eight", "ei8ght"),
-            ("nine", "ni9ne"),
-        ];
-
-        let mut copy = String::from(self);
-        for num_pair in number_strings {
-            copy = copy.replace(num_pair.0, num_pair.1);
+    pub fn with_zero(self) -> Vocabulary {
+        self.with_words(&[("zero", 0)])
+    }
+
+    /// Adds spelled-out digits, for example `[("eins", 1), ("zwei", 2)]`.
+    pub fn with_words(mut self, words: &[(&str, u32)]) -> Vocabulary {
+        for &(word, value) in words {
+            self.tokens.push(Token {
+                text: word.to_string(),
+                value,
+                kind: TokenKind::Word,
+            });
         }
-        copy
+        self
     }
 }
 
+/// A digit found in a line, with its byte range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitMatch {
+    pub start: usize,
+    pub end: usize,
+    pub value: u32,
+    pub kind: TokenKind,
+}
+
+/// Finds digits from a [`Vocabulary`] in a line, including overlapping ones like
+/// the "eight" and "two" in "eightwo".
+#[derive(Debug, Clone)]
+pub struct DigitScanner {
+    tokens: Vec<Token>,
+    forward: Automaton,
+    // built from the reversed spellings, to scan lines from their end
+    backward: Automaton,
+}
+
+impl DigitScanner {
+    pub fn new(vocabulary: &Vocabulary) -> DigitScanner {
+        let tokens = vocabulary.tokens.clone();
+        let reversed: Vec<Vec<u8>> = tokens
+            .iter()
+            .map(|t| t.text.bytes().rev().collect())
+            .collect();
+        DigitScanner {
+            forward: Automaton::new(
+                &tokens
+                    .iter()
+                    .map(|t| t.text.as_str())
+                    .collect::<Vec<&str>>(),
+            ),
+            backward: Automaton::new(&reversed),
+            tokens,
+        }
+    }
+
+    fn to_digit(&self, m: PatternMatch) -> DigitMatch {
+        let token = &self.tokens[m.pattern];
+        DigitMatch {
+            start: m.start,
+            end: m.end,
+            value: token.value,
+            kind: token.kind,
+        }
+    }
+
+    /// The digit that starts first, reading from the start of the line.
+    pub fn first(&self, line: &str) -> Option<DigitMatch> {
+        self.forward
+            .find_first(line.bytes())
+            .map(|m| self.to_digit(m))
+    }
+
+    /// The digit that ends last, reading from the end of the line.
+    pub fn last(&self, line: &str) -> Option<DigitMatch> {
+        let m = self.backward.find_first(line.bytes().rev())?;
+        Some(self.to_digit(PatternMatch {
+            pattern: m.pattern,
+            start: line.len() - m.end,
+            end: line.len() - m.start,
+        }))
+    }
+
+    /// Every digit in the line, overlapping ones included, in order.
+    pub fn all(&self, line: &str) -> Vec<DigitMatch> {
+        self.forward
+            .find_all(line.as_bytes())
+            .into_iter()
+            .map(|m| self.to_digit(m))
+            .collect()
+    }
+
+    /// The two-digit number made of the first and last digit, or 0 if there are none.
+    pub fn first_last_number(&self, line: &str) -> u32 {
+        match (self.first(line), self.last(line)) {
+            (Some(first), Some(last)) => first.value * 10 + last.value,
+            _ => 0,
+        }
+    }
+}
+
+/// How digits are recognised in a calibration line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Only the characters 0 to 9, as in part 1.
+    DigitsOnly,
+    /// Digits and the English words one to nine, as in part 2.
+    SpelledOut,
+}
+
+impl Strategy {
+    pub fn scanner(self) -> &'static DigitScanner {
+        static DIGITS_ONLY: OnceLock<DigitScanner> = OnceLock::new();
+        static SPELLED_OUT: OnceLock<DigitScanner> = OnceLock::new();
+        match self {
+            Strategy::DigitsOnly => {
+                DIGITS_ONLY.get_or_init(|| DigitScanner::new(&Vocabulary::digits()))
+            }
+            Strategy::SpelledOut => {
+                SPELLED_OUT.get_or_init(|| DigitScanner::new(&Vocabulary::english()))
+            }
+        }
+    }
+}
+
+pub trait StringHelpers {
+    fn get_first_last_number(&self, strategy: Strategy) -> u32;
+}
+
+impl StringHelpers for str {
+    fn get_first_last_number(&self, strategy: Strategy) -> u32 {
+        strategy.scanner().first_last_number(self)
+    }
+}
 
-pub fn sum_all_lines(lines: Vec<String>) -> u32 {
-    let sum = lines.iter().fold(0, |accumulator, s| {
-        let line_sum = s.get_first_last_number();
+pub fn sum_all_lines<I, S>(lines: I, strategy: Strategy) -> u32
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    lines.into_iter().fold(0, |accumulator, s| {
+        let line_sum = s.as_ref().get_first_last_number(strategy);
         accumulator + line_sum
-    });
-    sum
+    })
+}
+
+/// How a single line contributed to the calibration sum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineExplanation {
+    pub line: usize,
+    pub text: String,
+    pub first: Option<DigitMatch>,
+    pub last: Option<DigitMatch>,
+    pub value: u32,
+}
+
+impl LineExplanation {
+    fn token(&self, m: Option<DigitMatch>) -> &str {
+        m.map_or("", |m| &self.text[m.start..m.end])
+    }
+}
+
+pub fn explain<I, S>(lines: I, strategy: Strategy) -> Vec<LineExplanation>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let scanner = strategy.scanner();
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let text = text.as_ref();
+            LineExplanation {
+                line: i + 1,
+                text: text.to_string(),
+                first: scanner.first(text),
+                last: scanner.last(text),
+                value: text.get_first_last_number(strategy),
+            }
+        })
+        .collect()
+}
+
+/// One row per line, with byte ranges for the matched tokens, and the total at the end.
+pub fn explain_table(explanations: &[LineExplanation]) -> String {
+    let token_width = explanations
+        .iter()
+        .flat_map(|e| [e.token(e.first).len(), e.token(e.last).len()])
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    let cell = |e: &LineExplanation, m: Option<DigitMatch>| match m {
+        Some(m) => format!(
+            "{:<token_width$} {:>7} {:<5}",
+            e.token(Some(m)),
+            format!("{}..{}", m.start, m.end),
+            m.kind.name()
+        ),
+        None => format!("{:<token_width$} {:>7} {:<5}", "-", "", ""),
+    };
+
+    let mut out = format!(
+        "{:>5}  {:<token_width$} {:>7} {:<5}  {:<token_width$} {:>7} {:<5}  {:>5}  {}\n",
+        "line", "first", "at", "kind", "last", "at", "kind", "value", "text"
+    );
+    for e in explanations {
+        let _ = writeln!(
+            out,
+            "{:>5}  {}  {}  {:>5}  {}",
+            e.line,
+            cell(e, e.first),
+            cell(e, e.last),
+            e.value,
+            e.text
+        );
+    }
+    let total: u32 = explanations.iter().map(|e| e.value).sum();
+    let _ = writeln!(out, "total {}", total);
+    out
+}
+
+pub fn explain_json(explanations: &[LineExplanation]) -> String {
+    let token = |e: &LineExplanation, m: Option<DigitMatch>| match m {
+        Some(m) => format!(
+            "{{\"token\": {}, \"start\": {}, \"end\": {}, \"kind\": \"{}\"}}",
+            json_string(e.token(Some(m))),
+            m.start,
+            m.end,
+            m.kind.name()
+        ),
+        None => "null".to_string(),
+    };
+    let rows: Vec<String> = explanations
+        .iter()
+        .map(|e| {
+            format!(
+                "  {{\"line\": {}, \"text\": {}, \"first\": {}, \"last\": {}, \"value\": {}}}",
+                e.line,
+                json_string(&e.text),
+                token(e, e.first),
+                token(e, e.last),
+                e.value
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", rows.join(",\n"))
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "digits",
+        input: "\
+1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet",
+        part1: Some("142"),
+        part2: None,
+    },
+    Example {
+        name: "words",
+        input: "\
+two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen",
+        part1: Some("209"),
+        part2: Some("281"),
+    },
+];
+
+pub struct Day1 {
+    lines: Vec<String>,
+}
+
+impl Solution for Day1 {
+    fn parse_lines<I, S>(lines: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Ok(Day1 {
+            lines: lines.into_iter().map(|s| s.as_ref().to_string()).collect(),
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        Box::new(sum_all_lines(&self.lines, Strategy::DigitsOnly))
+    }
+
+    fn part2(&self) -> Answer {
+        Box::new(sum_all_lines(&self.lines, Strategy::SpelledOut))
+    }
 }
 
 #[cfg(test)]
@@ -51,33 +373,95 @@ mod tests {
     #[test]
     fn get_first_last_number_should_work() {
         let s = "abc123";
-        assert_eq!(s.get_first_last_number(), 13);
+        assert_eq!(s.get_first_last_number(Strategy::DigitsOnly), 13);
+        assert_eq!(s.get_first_last_number(Strategy::SpelledOut), 13);
     }
 
-
     #[test]
     fn day_1_works_with_multiple_lines() {
-        let test_val = "1abc2
-        pqr3stu8vwx
-        a1b2c3d4e5f
-        treb7uchet";
-
-        let lines = test_val.lines().map(|s| s.to_string()).collect();
-        assert_eq!(sum_all_lines(lines), 142);
+        assert_eq!(
+            sum_all_lines(EXAMPLES[0].input.lines(), Strategy::DigitsOnly),
+            142
+        );
     }
 
     #[test]
     fn day_1_works_with_words() {
-        let test_val = "\
-        two1nine
-eightwothree
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen";
+        let lines: Vec<String> = EXAMPLES[1].input.lines().map(|s| s.to_string()).collect();
+        assert_eq!(sum_all_lines(&lines, Strategy::SpelledOut), 281);
+        assert_eq!(sum_all_lines(&lines, Strategy::DigitsOnly), 209);
+    }
+
+    #[test]
+    fn digits_only_should_ignore_words() {
+        assert_eq!("eightwo1".get_first_last_number(Strategy::DigitsOnly), 11);
+        assert_eq!("eightwo1".get_first_last_number(Strategy::SpelledOut), 81);
+        assert_eq!("eightwo".get_first_last_number(Strategy::DigitsOnly), 0);
+    }
+
+    #[test]
+    fn overlapping_words_should_both_count() {
+        let scanner = DigitScanner::new(&Vocabulary::english());
+        assert_eq!(scanner.first_last_number("eightwo"), 82);
+        assert_eq!(scanner.first_last_number("xtwone3four"), 24);
+        assert_eq!(scanner.first_last_number("nothing"), 0);
+    }
+
+    #[test]
+    fn matches_should_report_positions_and_kinds() {
+        let scanner = DigitScanner::new(&Vocabulary::english());
+        let first = scanner.first("zoneight234").unwrap();
+        let last = scanner.last("zoneight234").unwrap();
+        assert_eq!(
+            first,
+            DigitMatch {
+                start: 1,
+                end: 4,
+                value: 1,
+                kind: TokenKind::Word
+            }
+        );
+        assert_eq!(
+            last,
+            DigitMatch {
+                start: 10,
+                end: 11,
+                value: 4,
+                kind: TokenKind::Digit
+            }
+        );
+        let values: Vec<u32> = scanner.all("oneight").iter().map(|m| m.value).collect();
+        assert_eq!(values, vec![1, 8]);
+    }
+
+    #[test]
+    fn vocabulary_should_be_configurable() {
+        let german = Vocabulary::digits().with_words(&[("eins", 1), ("zwei", 2), ("drei", 3)]);
+        let scanner = DigitScanner::new(&german);
+        assert_eq!(scanner.first_last_number("xzweinsx"), 21);
+        assert_eq!(scanner.first_last_number("two1nine"), 11);
+
+        let with_zero = DigitScanner::new(&Vocabulary::english().with_zero());
+        assert_eq!(with_zero.first_last_number("zerotwo"), 2);
+        assert_eq!(with_zero.first("zerotwo").unwrap().value, 0);
+    }
+
+    #[test]
+    fn explain_should_describe_each_line() {
+        let explanations = explain(["eightwo1", "abc"], Strategy::SpelledOut);
+        assert_eq!(explanations[0].value, 81);
+        assert_eq!(explanations[0].token(explanations[0].first), "eight");
+        assert_eq!(explanations[0].last.unwrap().kind, TokenKind::Digit);
+        assert_eq!(explanations[1].first, None);
+        assert_eq!(explanations[1].value, 0);
 
-        let lines = test_val.lines().map(|s| s.to_string()).collect();
-        assert_eq!(sum_all_lines(lines), 281);
+        assert_eq!(
+            explain_json(&explanations[..1]),
+            "[\n  {\"line\": 1, \"text\": \"eightwo1\", \
+             \"first\": {\"token\": \"eight\", \"start\": 0, \"end\": 5, \"kind\": \"word\"}, \
+             \"last\": {\"token\": \"1\", \"start\": 7, \"end\": 8, \"kind\": \"digit\"}, \
+             \"value\": 81}\n]\n"
+        );
+        assert!(explain_table(&explanations).ends_with("total 81\n"));
     }
 }
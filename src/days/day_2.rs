@@ -1,102 +1,347 @@
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::{self, Write};
+use std::str::FromStr;
 
-#[derive(PartialEq)]
-#[derive(Debug)]
+use crate::error::ParseError;
+use crate::solution::{checked_answer, Answer, Example, Solution};
+use crate::utilities::{json_string, parse_lines};
+
+/// A cube colour. The puzzle only uses red, green and blue, but any other
+/// colour name parses as [`Color::Other`]. Names are read in any case and kept
+/// in lowercase, so a colour always prints the way it parses back.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+    Other(OtherColor),
+}
+
+impl Color {
+    /// The colour called `name`, in any case.
+    pub fn named(name: &str) -> Color {
+        let name = name.to_lowercase();
+        match name.as_str() {
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "blue" => Color::Blue,
+            _ => Color::Other(OtherColor(name)),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Color, Infallible> {
+        Ok(Color::named(s))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Red => f.write_str("red"),
+            Color::Green => f.write_str("green"),
+            Color::Blue => f.write_str("blue"),
+            Color::Other(other) => f.write_str(other.name()),
+        }
+    }
+}
+
+/// The lowercase name of a colour other than red, green and blue, which have
+/// their own [`Color`] variants.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OtherColor(String);
+
+impl OtherColor {
+    /// `None` if `name` is red, green or blue in any case.
+    pub fn new(name: &str) -> Option<OtherColor> {
+        match Color::named(name) {
+            Color::Other(other) => Some(other),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A handful of cubes, counted by colour. Colours with no cubes are left out, so
+/// pulls compare equal however they were built.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "BTreeMap<Color, u32>", into = "BTreeMap<Color, u32>")
+)]
 pub struct Pull {
-    blue: u32,
-    red: u32,
-    green: u32,
+    cubes: BTreeMap<Color, u32>,
 }
 
 impl Pull {
-
     pub fn new(red: u32, green: u32, blue: u32) -> Pull {
-        Pull {
-            red,
-            green,
-            blue,
-        }
+        Pull::default()
+            .with(Color::Red, red)
+            .with(Color::Green, green)
+            .with(Color::Blue, blue)
+    }
+
+    pub fn with(mut self, color: Color, count: u32) -> Pull {
+        self.set(color, count);
+        self
     }
 
-    fn from_string(pull_text: &str) -> Pull {
-        let mut pull = Pull {
-            blue: 0,
-            red: 0,
-            green: 0,
+    fn set(&mut self, color: Color, count: u32) {
+        match count {
+            0 => self.cubes.remove(&color),
+            _ => self.cubes.insert(color, count),
         };
+    }
+
+    pub fn get(&self, color: &Color) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    /// Each colour in the pull with its count, in colour order.
+    pub fn cubes(&self) -> impl Iterator<Item = (&Color, u32)> {
+        self.cubes.iter().map(|(color, &count)| (color, count))
+    }
+
+    /// The product of the red, green and blue counts and of any other colours
+    /// present. A missing primary colour makes it 0, as in the puzzle. `None` if
+    /// the product does not fit in a `u64`, which only takes a few colours.
+    pub fn power(&self) -> Option<u64> {
+        let primaries = [Color::Red, Color::Green, Color::Blue];
+        let others = self
+            .cubes
+            .iter()
+            .filter(|(color, _)| !primaries.contains(color))
+            .map(|(_, &count)| count);
+        primaries
+            .iter()
+            .map(|c| self.get(c))
+            .chain(others)
+            .try_fold(1u64, |product, count| product.checked_mul(count as u64))
+    }
+
+    fn from_string(pull_text: &str) -> Result<Pull, ParseError> {
+        let mut pull = Pull::default();
+        // zero counts are not kept in the pull, so track every colour seen here
+        let mut seen = vec![];
 
         for color_text in pull_text.split(",") {
-            let components: Vec<&str> = color_text.trim().split(" ").collect();
-            let value = components[0].parse().unwrap();
-            let color = components[1];
-            match color {
-                "blue" => pull.blue = value,
-                "red" => pull.red = value,
-                "green" => pull.green = value,
-                c => panic!("Unknown color in pull {}", c),
+            let color_text = color_text.trim();
+            let (value, color) = color_text.split_once(" ").ok_or_else(|| {
+                ParseError::new(pull_text, color_text, "a cube count followed by a colour")
+            })?;
+            let value = value
+                .parse()
+                .map_err(|_| ParseError::new(pull_text, value, "a cube count"))?;
+            if color.is_empty() || !color.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(ParseError::new(pull_text, color, "a colour"));
+            }
+            let named = Color::named(color);
+            if seen.contains(&named) {
+                return Err(ParseError::new(pull_text, color, "a colour not already in the pull"));
             }
+            seen.push(named.clone());
+            pull.set(named, value);
         }
 
-        pull
+        // an empty pull would print as nothing, which does not parse back
+        if pull.cubes.is_empty() {
+            return Err(ParseError::new(pull_text, pull_text.trim(), "at least one cube"));
+        }
+        Ok(pull)
+    }
+}
+
+impl From<BTreeMap<Color, u32>> for Pull {
+    fn from(cubes: BTreeMap<Color, u32>) -> Pull {
+        cubes
+            .into_iter()
+            .fold(Pull::default(), |pull, (color, count)| {
+                pull.with(color, count)
+            })
     }
 }
 
+impl From<Pull> for BTreeMap<Color, u32> {
+    fn from(pull: Pull) -> BTreeMap<Color, u32> {
+        pull.cubes
+    }
+}
 
-struct Game {
+impl FromStr for Pull {
+    type Err = ParseError;
+
+    /// Parses a pull like `3 blue, 4 red`.
+    fn from_str(s: &str) -> Result<Pull, ParseError> {
+        Pull::from_string(s)
+    }
+}
+
+/// Writes the pull the way the puzzle does, e.g. `4 red, 3 blue`.
+impl fmt::Display for Pull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (color, count)) in self.cubes().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{} {}", count, color)?;
+        }
+        Ok(())
+    }
+}
+
+/// Colours are written by name, so they can be used as map keys.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Color::named(&name))
+    }
+}
+
+/// One game: its id and the pulls shown from the bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
     id: u32,
     pulls: Vec<Pull>,
 }
+
 impl Game {
+    pub fn new(id: u32, pulls: Vec<Pull>) -> Game {
+        Game { id, pulls }
+    }
 
-    fn min_cubes(&self) -> Pull {
-        let mut min_pull = Pull::new(0,0,0);
-        for pull in &self.pulls {
-            if pull.red > min_pull.red {
-                min_pull.red = pull.red;
-            }
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn pulls(&self) -> &[Pull] {
+        &self.pulls
+    }
 
-            if pull.green > min_pull.green {
-                min_pull.green = pull.green;
+    /// The fewest cubes of each colour that could have produced the game.
+    pub fn min_cubes(&self) -> Pull {
+        let mut min_pull = Pull::default();
+        for pull in &self.pulls {
+            for (color, &count) in &pull.cubes {
+                if count > min_pull.get(color) {
+                    min_pull.set(color.clone(), count);
+                }
             }
-             if pull.blue > min_pull.blue {
-                 min_pull.blue = pull.blue;
-             }
         }
         min_pull
     }
 
-    fn is_valid(&self, max_pull: &Pull) -> bool {
-        for pull in self.pulls.iter() {
-        if pull.green > max_pull.green || pull.red > max_pull.red || pull.blue > max_pull.blue {
-                return false;
-            }
-        }
-        return true
-    }
-    fn from_line(line: &String) -> Game {
-        let mut id_and_pulls = line.split(":");
-        let id: u32 = id_and_pulls.next()
-            .unwrap()
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .collect::<String>()
+    pub fn is_valid(&self, max_pull: &Pull) -> bool {
+        self.rejections(max_pull).next().is_none()
+    }
+
+    /// Every colour of every pull that needs more cubes than `bag` holds.
+    pub fn rejections<'a>(&'a self, bag: &'a Pull) -> impl Iterator<Item = Rejection> + 'a {
+        self.pulls.iter().enumerate().flat_map(move |(i, pull)| {
+            pull.cubes
+                .iter()
+                .filter(|(color, &count)| count > bag.get(color))
+                .map(move |(color, &count)| Rejection {
+                    game: self.id,
+                    pull: i + 1,
+                    color: color.clone(),
+                    count,
+                    limit: bag.get(color),
+                })
+        })
+    }
+
+    /// The most cubes shown at once, across all colours.
+    pub fn max_total_cubes(&self) -> u64 {
+        self.pulls
+            .iter()
+            .map(|pull| pull.cubes.values().map(|&count| count as u64).sum())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn from_line(line: &str) -> Result<Game, ParseError> {
+        let (header, pulls_text) = line
+            .split_once(":")
+            .ok_or_else(|| ParseError::new(line, &line[line.len()..], "':' after the game id"))?;
+        let id_text = header
+            .trim()
+            .strip_prefix("Game")
+            .ok_or_else(|| ParseError::new(line, header.trim(), "\"Game\""))?
+            .trim();
+        let id: u32 = id_text
             .parse()
-            .unwrap();
+            .map_err(|_| ParseError::new(line, id_text, "a game id"))?;
 
-        let pulls: Vec<Pull> = id_and_pulls
-            .next()
-            .unwrap()
+        let pulls = pulls_text
             .split(";")
-            .map(|p| Pull::from_string(p)).collect();
+            .map(|p| Pull::from_string(p).map_err(|e| e.inside(line, p)))
+            .collect::<Result<Vec<Pull>, ParseError>>()?;
 
+        Ok(Game { id, pulls })
+    }
+}
 
-        Game { id, pulls }
+/// Writes the game as a puzzle input line, e.g. `Game 1: 4 red, 3 blue; 2 green`.
+/// Zero counts are left out. Parsing rejects pulls with no cubes, so every parsed
+/// game reads back the same.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}:", self.id)?;
+        for (i, pull) in self.pulls.iter().enumerate() {
+            let separator = if i > 0 { ";" } else { "" };
+            write!(f, "{} {}", separator, pull)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
+
+    /// Parses a line like `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+    fn from_str(s: &str) -> Result<Game, ParseError> {
+        Game::from_line(s)
     }
 }
 
-pub fn sum_valid_ids_from_lines(lines: &Vec<String>, max_pull: &Pull) -> u32 {
+pub fn sum_valid_ids_from_lines<I, S>(lines: I, max_pull: &Pull) -> Result<u32, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let games = parse_lines(lines, Game::from_line)?;
+    Ok(sum_valid_ids(&games, max_pull))
+}
+
+pub fn power_from_lines<I, S>(lines: I) -> Result<Option<u64>, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let games = parse_lines(lines, Game::from_line)?;
+    Ok(total_power(&games))
+}
+
+fn sum_valid_ids(games: &[Game], max_pull: &Pull) -> u32 {
     let mut sum = 0;
-    for line in lines {
-        let game = Game::from_line(line);
+    for game in games {
         if game.is_valid(max_pull) {
             sum += game.id;
         }
@@ -104,79 +349,436 @@ pub fn sum_valid_ids_from_lines(lines: &Vec<String>, max_pull: &Pull) -> u32 {
     sum
 }
 
-pub fn power_from_lines(lines: &Vec<String>) -> u32 {
-    let mut total = 0;
-    for line in lines {
-        let game = Game::from_line(line).min_cubes();
-        let power = game.blue * game.red * game.green;
-        total += power;
+/// The sum of every game's power, or `None` if it does not fit in a `u64`.
+fn total_power(games: &[Game]) -> Option<u64> {
+    let mut total: u64 = 0;
+    for game in games {
+        total = total.checked_add(game.min_cubes().power()?)?;
+    }
+    Some(total)
+}
+
+/// Why a bag cannot produce a game: one of its pulls shows more cubes of a
+/// colour than the bag holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub game: u32,
+    /// Which pull of the game, counting from 1.
+    pub pull: usize,
+    pub color: Color,
+    pub count: u32,
+    pub limit: u32,
+}
+
+/// The bag from the puzzle: 12 red, 13 green and 14 blue cubes.
+pub fn puzzle_bag() -> Pull {
+    Pull::new(12, 13, 14)
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "games",
+    input: "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+    part1: Some("8"),
+    part2: Some("2286"),
+}];
+
+pub struct Day2 {
+    games: Vec<Game>,
+}
+
+impl Day2 {
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    /// The smallest bag that could have produced every game.
+    pub fn minimal_bag(&self) -> Pull {
+        let mut bag = Pull::default();
+        for game in &self.games {
+            for (color, count) in game.min_cubes().cubes {
+                if count > bag.get(&color) {
+                    bag.set(color, count);
+                }
+            }
+        }
+        bag
+    }
+
+    /// Why each game that `bag` could not have produced was rejected.
+    pub fn rejections(&self, bag: &Pull) -> Vec<Rejection> {
+        self.games.iter().flat_map(|g| g.rejections(bag)).collect()
+    }
+
+    /// Each game's id with the most cubes it showed in a single pull.
+    pub fn max_totals(&self) -> Vec<(u32, u64)> {
+        self.games
+            .iter()
+            .map(|g| (g.id, g.max_total_cubes()))
+            .collect()
+    }
+
+    /// A readable summary of every query against `bag`.
+    pub fn analysis_table(&self, bag: &Pull) -> String {
+        let mut out = format!("minimal bag: {}\n", self.minimal_bag());
+        let _ = writeln!(out, "{:>5}  {:>9}  verdict", "game", "max cubes");
+        // each game's own rejections, as ids need not be unique
+        for (game, (id, max)) in self.games.iter().zip(self.max_totals()) {
+            let reasons: Vec<String> = game
+                .rejections(bag)
+                .map(|r| format!("pull {}: {} {} > {}", r.pull, r.count, r.color, r.limit))
+                .collect();
+            let verdict = match reasons.is_empty() {
+                true => "possible".to_string(),
+                false => format!("impossible ({})", reasons.join("; ")),
+            };
+            let _ = writeln!(out, "{:>5}  {:>9}  {}", id, max, verdict);
+        }
+        out
+    }
+
+    pub fn analysis_json(&self, bag: &Pull) -> String {
+        let counts = |pull: &Pull| {
+            let fields: Vec<String> = pull
+                .cubes
+                .iter()
+                .map(|(color, count)| format!("{}: {}", json_string(&color.to_string()), count))
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        };
+        let games: Vec<String> = self
+            .games
+            .iter()
+            .zip(self.max_totals())
+            .map(|(game, (id, max))| {
+                let reasons: Vec<String> = game
+                    .rejections(bag)
+                    .map(|r| {
+                        format!(
+                            "{{\"pull\": {}, \"color\": {}, \"count\": {}, \"limit\": {}}}",
+                            r.pull,
+                            json_string(&r.color.to_string()),
+                            r.count,
+                            r.limit
+                        )
+                    })
+                    .collect();
+                format!(
+                    "    {{\"id\": {}, \"max_cubes\": {}, \"rejections\": [{}]}}",
+                    id,
+                    max,
+                    reasons.join(", ")
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"bag\": {},\n  \"minimal_bag\": {},\n  \"games\": [\n{}\n  ]\n}}\n",
+            counts(bag),
+            counts(&self.minimal_bag()),
+            games.join(",\n")
+        )
+    }
+}
+
+impl Solution for Day2 {
+    fn parse_lines<I, S>(lines: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Ok(Day2 {
+            games: parse_lines(lines, Game::from_line)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        Box::new(sum_valid_ids(&self.games, &puzzle_bag()))
+    }
+
+    fn part2(&self) -> Answer {
+        checked_answer(total_power(&self.games))
     }
-    total
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solution::TOO_LARGE;
 
     #[test]
     fn min_game_should_be_correct() {
         let line = String::from("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
-        let game = Game::from_line(&line);
+        let game = Game::from_line(&line).unwrap();
 
         assert_eq!(game.min_cubes(), Pull::new(4, 2, 6));
     }
 
     #[test]
     fn valid_lines_should_be_summed() {
-        let lines: Vec<String> = "\
-        Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"
-            .lines().map(|s| s.to_string()).collect();
+        let lines: Vec<String> = EXAMPLES[0].input.lines().map(|s| s.to_string()).collect();
 
-        let max_pull = Pull {
-            red: 12,
-            blue: 14,
-            green: 13
-        };
+        let max_pull = Pull::new(12, 13, 14);
 
-        let sum = sum_valid_ids_from_lines(&lines, &max_pull );
+        let sum = sum_valid_ids_from_lines(&lines, &max_pull).unwrap();
         assert_eq!(sum, 8);
     }
 
     #[test]
     fn test_game_should_identify_impossible_games() {
         let line = String::from("Game 1: 3 blue, 1 red; 2 red, 2 green, 6 blue; 2 green");
-        let game = Game::from_line(&line);
-        let max_pull = Pull {
-            red: 1,
-            green: 13,
-            blue: 14
-        };
-        let valid_pull = Pull {
-            red: 10,
-            green: 13,
-            blue: 14,
-        };
+        let game = Game::from_line(&line).unwrap();
+        let max_pull = Pull::new(1, 13, 14);
+        let valid_pull = Pull::new(10, 13, 14);
 
-        assert_eq!(game.is_valid(&max_pull), false);
-        assert_eq!(game.is_valid(&valid_pull), true);
+        assert!(!game.is_valid(&max_pull));
+        assert!(game.is_valid(&valid_pull));
     }
 
     #[test]
     fn parse_line_should_return_games_and_id() {
         let line = String::from("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
-        let game = Game::from_line(&line);
-
+        let game = Game::from_line(&line).unwrap();
 
         assert_eq!(game.pulls.len(), 3);
         assert_eq!(game.id, 1);
-        assert_eq!(game.pulls[0], Pull {
-            blue: 3,
-            red: 4,
-            green: 0
-        });
+        assert_eq!(game.pulls[0], Pull::new(4, 0, 3));
+    }
+
+    #[test]
+    fn bad_colour_should_report_position() {
+        let err = Game::from_line("Game 3: 1 red; 2 pur-ple").unwrap_err();
+        assert_eq!(err.column, 18);
+        assert_eq!(err.found, "pur-ple");
+        assert_eq!(err.expected, "a colour");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn extra_colours_should_parse() {
+        let game = Game::from_line("Game 7: 2 yellow, 1 red; 3 purple, 4 yellow").unwrap();
+        let yellow = Color::named("yellow");
+        let purple: Color = "purple".parse().unwrap();
+        assert_eq!(
+            game.min_cubes(),
+            Pull::new(1, 0, 0).with(yellow.clone(), 4).with(purple, 3)
+        );
+        assert!(!game.is_valid(&puzzle_bag()));
+        assert!(game.is_valid(
+            &puzzle_bag()
+                .with(yellow, 4)
+                .with(Color::Other(OtherColor::new("purple").unwrap()), 3)
+        ));
+        assert_eq!(game.min_cubes().power(), Some(0));
+        assert_eq!(
+            Pull::new(1, 2, 3)
+                .with(Color::named("pink"), 4)
+                .power(),
+            Some(24)
+        );
+    }
+
+    #[test]
+    fn powers_too_big_to_count_should_be_none() {
+        let day = Day2::parse("Game 1: 5000 red, 5000 green, 5000 blue").unwrap();
+        assert_eq!(day.part2().to_string(), "125000000000");
+
+        let colours = ["red", "green", "blue", "a", "b", "c", "d", "e"];
+        let pull: Vec<String> = colours.iter().map(|c| format!("4000 {}", c)).collect();
+        let game = Game::from_line(&format!("Game 1: {}", pull.join(", "))).unwrap();
+        assert_eq!(game.min_cubes().power(), None);
+        let day = Day2 { games: vec![game] };
+        assert_eq!(day.part2().to_string(), TOO_LARGE);
+
+        // each power fits, but not their sum
+        let line = "Game 1: 4000000000 red, 4000000000 green, 1 blue";
+        let day = Day2::parse(&[line, line].join("\n")).unwrap();
+        assert_eq!(day.part2().to_string(), TOO_LARGE);
+    }
+
+    #[test]
+    fn colours_should_ignore_case() {
+        let game = Game::from_line("Game 1: 3 Red, 2 BLUE; 1 Yellow").unwrap();
+        assert_eq!(
+            game.min_cubes(),
+            Pull::new(3, 0, 2).with(Color::named("yellow"), 1)
+        );
+        assert!(game.is_valid(&puzzle_bag().with(Color::named("yellow"), 1)));
+        assert_eq!(game.to_string(), "Game 1: 3 red, 2 blue; 1 yellow");
+
+        assert_eq!(OtherColor::new("Red"), None);
+        assert_eq!(OtherColor::new("Teal").unwrap().name(), "teal");
+        let teal = Color::named("TEAL");
+        assert_eq!(teal.to_string().parse::<Color>(), Ok(teal));
+    }
+
+    #[test]
+    fn analysis_should_keep_games_with_the_same_id_apart() {
+        let day = Day2::parse("Game 1: 20 red\nGame 1: 1 red").unwrap();
+        let table = day.analysis_table(&puzzle_bag());
+        assert!(table.contains("    1         20  impossible (pull 1: 20 red > 12)\n"));
+        assert!(table.ends_with("    1          1  possible\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&day.analysis_json(&puzzle_bag())).unwrap();
+        assert_eq!(json["games"][0]["rejections"].as_array().unwrap().len(), 1);
+        assert_eq!(json["games"][1]["rejections"].as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn max_totals_should_not_overflow() {
+        let day = Day2::parse("Game 1: 4000000000 red, 4000000000 green").unwrap();
+        assert_eq!(day.max_totals(), vec![(1, 8000000000)]);
+    }
+
+    #[test]
+    fn repeated_colours_should_be_rejected() {
+        let err = Game::from_line("Game 1: 20 red, 5 red").unwrap_err();
+        assert_eq!(err.column, 19);
+        assert_eq!(err.found, "red");
+        assert_eq!(err.expected, "a colour not already in the pull");
+
+        let err = Game::from_line("Game 1: 1 blue; 0 Red, 2 RED").unwrap_err();
+        assert_eq!((err.column, err.found.as_str()), (26, "RED"));
+        assert!(Game::from_line("Game 1: 1 red; 2 red").is_ok());
+    }
+
+    #[test]
+    fn pulls_without_cubes_should_be_rejected() {
+        let err = Game::from_line("Game 1: 2 red; 0 red, 0 blue").unwrap_err();
+        assert_eq!(err.column, 16);
+        assert_eq!(err.found, "0 red, 0 blue");
+        assert_eq!(err.expected, "at least one cube");
+
+        let game = Game::from_line("Game 1: 0 red, 2 blue").unwrap();
+        assert_eq!(game.to_string(), "Game 1: 2 blue");
+    }
+
+    #[test]
+    fn bad_line_should_report_line_number() {
+        let input = "Game 1: 1 red\nGame two: 2 blue";
+        let err = Day2::parse(input).err().unwrap();
+        assert_eq!(err.line, Some(2));
+        assert_eq!(err.column, 6);
+        assert_eq!(err.expected, "a game id");
+    }
+
+    #[test]
+    fn queries_should_explain_the_example() {
+        let day = Day2::parse(EXAMPLES[0].input).unwrap();
+        assert_eq!(day.minimal_bag(), Pull::new(20, 13, 15));
+        assert_eq!(
+            day.rejections(&puzzle_bag()),
+            vec![
+                Rejection {
+                    game: 3,
+                    pull: 1,
+                    color: Color::Red,
+                    count: 20,
+                    limit: 12
+                },
+                Rejection {
+                    game: 4,
+                    pull: 3,
+                    color: Color::Red,
+                    count: 14,
+                    limit: 12
+                },
+                Rejection {
+                    game: 4,
+                    pull: 3,
+                    color: Color::Blue,
+                    count: 15,
+                    limit: 14
+                },
+            ]
+        );
+        assert_eq!(
+            day.max_totals(),
+            vec![(1, 9), (2, 8), (3, 34), (4, 32), (5, 10)]
+        );
+        assert!(day.rejections(&day.minimal_bag()).is_empty());
+        assert!(day
+            .analysis_table(&puzzle_bag())
+            .contains("    4         32  impossible (pull 3: 14 red > 12; pull 3: 15 blue > 14)"));
+    }
+
+    #[test]
+    fn games_should_parse_with_from_str() {
+        let game: Game = "Game 12: 3 blue, 4 red; 2 green".parse().unwrap();
+        assert_eq!(game.id(), 12);
+        assert_eq!(game.pulls()[1], "2 green".parse().unwrap());
+        let counts: Vec<(&Color, u32)> = game.pulls()[0].cubes().collect();
+        assert_eq!(counts, vec![(&Color::Red, 4), (&Color::Blue, 3)]);
+        assert!("Game x: 1 red".parse::<Game>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn games_should_round_trip_through_json() {
+        let game = Game::new(
+            3,
+            vec![Pull::new(1, 0, 2).with("yellow".parse().unwrap(), 5)],
+        );
+        let json = serde_json::to_string(&game).unwrap();
+        assert_eq!(json, r#"{"id":3,"pulls":[{"red":1,"blue":2,"yellow":5}]}"#);
+        assert_eq!(serde_json::from_str::<Game>(&json).unwrap(), game);
+    }
+
+    #[test]
+    fn display_should_write_the_puzzle_format() {
+        let game: Game = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            game.to_string(),
+            "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green"
+        );
+    }
+
+    #[test]
+    fn parse_print_parse_should_be_the_identity() {
+        const COLOURS: [&str; 6] = ["red", "Green", "blue", "yellow", "PURPLE", "teal"];
+        // a fixed xorshift sequence, so failures can be reproduced
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |below: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % below
+        };
+
+        for _ in 0..500 {
+            let pulls: Vec<String> = (0..1 + next(5))
+                .map(|_| {
+                    // consecutive colours from a random start, so none repeats
+                    let start = next(6);
+                    let counts: Vec<String> = (0..1 + next(4))
+                        .map(|c| format!("{} {}", next(31), COLOURS[((start + c) % 6) as usize]))
+                        .collect();
+                    counts.join(", ")
+                })
+                .collect();
+            let line = format!("Game {}: {}", next(1000), pulls.join("; "));
+
+            let game: Game = match line.parse() {
+                Ok(game) => game,
+                Err(e) => {
+                    assert_eq!(e.expected, "at least one cube", "{}", line);
+                    continue;
+                }
+            };
+            let printed = game.to_string();
+            assert_eq!(
+                printed.parse::<Game>().unwrap(),
+                game,
+                "{} printed as {}",
+                line,
+                printed
+            );
+            assert_eq!(printed.parse::<Game>().unwrap().to_string(), printed);
+        }
+    }
+}
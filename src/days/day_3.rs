@@ -1,310 +1,547 @@
-use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{Debug, Write};
+use std::ops::Range;
 
-use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
-
-#[derive(Debug, Clone)]
-struct OutOfBoundsError;
+use crate::error::ParseError;
+use crate::grid::{Grid, Pos};
+use crate::solution::{checked_answer, Answer, Example, Solution, TOO_LARGE};
+use crate::utilities::json_string;
 
 #[derive(Debug)]
 pub struct Engine {
-    schematic: Vec<Vec<char>>,
+    schematic: Grid<char>,
+    rules: Rules,
+    index: Index,
 }
 
-#[derive(Debug, PartialEq, Hash, Eq, Clone, Copy)]
-struct Pos {
-    x: usize,
-    y: usize,
+/// Which neighbours of a digit count as touching it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Adjacency {
+    /// Only the cells above, below, left and right.
+    Four,
+    /// Diagonals too, as in the puzzle.
+    #[default]
+    Eight,
 }
 
-#[derive(Debug, PartialEq)]
-struct CharPos {
-    c: char,
-    pos: Pos,
+/// Which cells count as symbols.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Symbols {
+    /// Anything that is neither a digit nor the empty cell, as in the puzzle.
+    #[default]
+    AnyOther,
+    Only(Vec<char>),
 }
 
-impl CharPos {
-    fn default() -> CharPos {
-        CharPos {
-            c: '.',
-            pos: Pos { x: 0, y: 0 },
-        }
-    }
+/// How to read a schematic. The default is the puzzle's reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub symbols: Symbols,
+    /// The character of empty cells. Cells outside the schematic read as empty.
+    pub empty: char,
+    /// The character of gears. It only counts where it is also a symbol, so
+    /// `Symbols::Only` must list it for there to be any gears.
+    pub gear: char,
+    /// How many numbers a gear must touch exactly.
+    pub gear_arity: usize,
+    pub adjacency: Adjacency,
 }
 
-impl Default for CharPos {
+impl Default for Rules {
     fn default() -> Self {
-        Self::default()
+        Rules {
+            symbols: Symbols::AnyOther,
+            empty: '.',
+            gear: '*',
+            gear_arity: 2,
+            adjacency: Adjacency::Eight,
+        }
     }
 }
 
-impl Clone for CharPos {
-    fn clone(&self) -> Self {
-        CharPos {
-            c: self.c.clone(),
-            pos: Pos {
-                x: self.pos.x.clone(),
-                y: self.pos.y.clone(),
-            },
+impl Rules {
+    pub fn is_symbol(&self, c: char) -> bool {
+        match &self.symbols {
+            Symbols::AnyOther => !c.is_ascii_digit() && c != self.empty,
+            Symbols::Only(symbols) => symbols.contains(&c),
         }
     }
 }
 
-#[derive(Debug, EnumIter, PartialEq)]
-enum Direction {
-    LUp,
-    Up,
-    RUp,
-    L,
-    R,
-    LDown,
-    Down,
-    RDown,
-    Current,
+/// A symbol cell of the schematic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub c: char,
+    pub pos: Pos,
 }
 
-enum PositionInNumber {
-    Start,
-    Middle,
-    End,
-    Single,
+/// A number in the schematic, with the symbols next to it in reading order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchematicNumber {
+    pub value: usize,
+    pub row: usize,
+    pub columns: Range<usize>,
+    pub symbols: Vec<Symbol>,
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Self::Current
+impl SchematicNumber {
+    /// Whether the number is a part number, i.e. touches a symbol.
+    pub fn is_part(&self) -> bool {
+        !self.symbols.is_empty()
     }
 }
 
-#[derive(Debug)]
-struct NumberInLine {
-    value: String,
-    x: usize,
+/// A number found while labelling the schematic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Span {
+    value: usize,
+    row: usize,
+    columns: Range<usize>,
 }
 
-fn create_number_in_line(buffer: &mut Vec<&char>, index: usize) -> NumberInLine {
-    let value = buffer.iter().cloned().collect::<String>();
-    let len = value.len();
-    buffer.clear();
-    NumberInLine {
-        value,
-        x: index - len,
-    }
+/// Which numbers each symbol touches, worked out once when the engine is built.
+#[derive(Debug)]
+struct Index {
+    numbers: Vec<Span>,
+    /// The number each cell belongs to, if any.
+    labels: Grid<Option<usize>>,
+    /// Every symbol in reading order, with the indices of the numbers it touches.
+    symbols: Vec<(Symbol, Vec<usize>)>,
 }
 
-fn parse_line_into_numbers(line: &Vec<char>) -> Vec<NumberInLine> {
-    let mut buffer = vec![];
-    let mut numbers = vec![];
-
-    for (index, c) in line.into_iter().enumerate() {
-        if c.is_digit(10) {
-            buffer.push(c)
-        } else if !buffer.is_empty() {
-            numbers.push(create_number_in_line(&mut buffer, index));
+impl Index {
+    /// Labels every cell with the number it belongs to and notes the symbols on
+    /// the way, in a single pass over the grid. Each symbol then only has to look
+    /// at its own neighbours. Fails if a number is too big to count with.
+    fn build(schematic: &Grid<char>, rules: &Rules) -> Result<Index, ParseError> {
+        let mut labels: Grid<Option<usize>> =
+            Grid::new(schematic.width(), schematic.height(), None);
+        let mut numbers = vec![];
+        let mut symbol_cells = vec![];
+
+        for (y, row) in schematic.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if !row[x].is_ascii_digit() {
+                    if rules.is_symbol(row[x]) {
+                        symbol_cells.push(Symbol {
+                            c: row[x],
+                            pos: Pos { x, y },
+                        });
+                    }
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                let mut value = Some(0usize);
+                while x < row.len() && row[x].is_ascii_digit() {
+                    let digit = row[x].to_digit(10).unwrap() as usize;
+                    value = value
+                        .and_then(|v| v.checked_mul(10))
+                        .and_then(|v| v.checked_add(digit));
+                    labels[Pos { x, y }] = Some(numbers.len());
+                    x += 1;
+                }
+                let Some(value) = value else {
+                    return Err(too_big(row, y, start..x));
+                };
+                numbers.push(Span {
+                    value,
+                    row: y,
+                    columns: start..x,
+                });
+            }
         }
+
+        let symbols = Index::link(&labels, symbol_cells, rules);
+        Ok(Index {
+            numbers,
+            labels,
+            symbols,
+        })
     }
-    if !buffer.is_empty() {
-        numbers.push(create_number_in_line(&mut buffer, line.len()));
-    }
-    numbers
-}
 
-impl Engine {
-    pub fn sum_gear_ratios(&self) -> usize {
-        let gears = self.find_gears();
-        let mut sum = 0;
-        for (_pos, gear) in gears {
-            if gear.len() == 2 {
-                sum += gear[0].parse::<usize>().unwrap() * gear[1].parse::<usize>().unwrap();
-            }
-        }
-        sum
+    /// Finds the symbols again under new rules. The numbers do not depend on the
+    /// rules, so they are kept.
+    fn relink(&mut self, schematic: &Grid<char>, rules: &Rules) {
+        let symbol_cells = schematic
+            .rows()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter().enumerate().filter_map(move |(x, &c)| {
+                    rules.is_symbol(c).then_some(Symbol { c, pos: Pos { x, y } })
+                })
+            })
+            .collect();
+        self.symbols = Index::link(&self.labels, symbol_cells, rules);
     }
 
-    pub fn from_string(input: String) -> Engine {
-        let schematic: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-        Engine { schematic }
+    /// Pairs each symbol with the numbers next to it.
+    fn link(
+        labels: &Grid<Option<usize>>,
+        symbol_cells: Vec<Symbol>,
+        rules: &Rules,
+    ) -> Vec<(Symbol, Vec<usize>)> {
+        symbol_cells
+            .into_iter()
+            .map(|symbol| {
+                let mut touching: Vec<usize> = match rules.adjacency {
+                    Adjacency::Four => labels
+                        .neighbours4(symbol.pos)
+                        .filter_map(|(_, l)| *l)
+                        .collect(),
+                    Adjacency::Eight => labels
+                        .neighbours8(symbol.pos)
+                        .filter_map(|(_, l)| *l)
+                        .collect(),
+                };
+                // a number can touch the same symbol through several digits
+                touching.sort_unstable();
+                touching.dedup();
+                (symbol, touching)
+            })
+            .collect()
     }
+}
 
-    fn find_gears(&self) -> HashMap<Pos, Vec<String>> {
-        let mut gears = HashMap::new();
+/// The error for the number in `columns` of row `y` not fitting in a `usize`.
+fn too_big(row: &[char], y: usize, columns: Range<usize>) -> ParseError {
+    let line: String = row.iter().collect();
+    let byte = |column: usize| row[..column].iter().map(|c| c.len_utf8()).sum::<usize>();
+    let number = &line[byte(columns.start)..byte(columns.end)];
+    ParseError::new(&line, number, format!("a number up to {}", usize::MAX)).on_line(y + 1, &line)
+}
 
-        for (i, line) in self.schematic.iter().enumerate() {
-            for num_in_line in parse_line_into_numbers(line) {
-                let pos = Pos {
-                    x: num_in_line.x,
-                    y: i,
-                };
-                self.find_gear_around_number(&mut gears, &pos, &num_in_line.value);
-            }
-        }
-        gears
+impl Engine {
+    /// The sum of every gear's ratio, or `None` if it does not fit in a `usize`.
+    pub fn sum_gear_ratios(&self) -> Option<usize> {
+        self.gear_symbols()
+            .try_fold(0usize, |sum, (_, numbers)| sum.checked_add(self.ratio(numbers)?))
     }
 
-    pub fn sum_valid_nums(&self) -> usize {
-        self.schematic
+    /// The product of `numbers`, or `None` if it does not fit in a `usize`.
+    fn ratio(&self, numbers: &[usize]) -> Option<usize> {
+        numbers
             .iter()
-            .enumerate()
-            .flat_map(|(i, line)| {
-                parse_line_into_numbers(line)
-                    .into_iter()
-                    .filter(move |num_in_line| {
-                        self.test_around_number(num_in_line.x, i, &num_in_line.value)
-                    })
-            })
-            .map(|num| num.value.parse::<usize>().unwrap())
-            .sum()
+            .try_fold(1usize, |product, &n| product.checked_mul(self.index.numbers[n].value))
     }
 
-    fn get_characters_surrounding_number<'a>(&'a self, pos: &Pos, val: &'a String) -> Vec<CharPos> {
-        //special case for single digit numbers
-        if val.len() == 1 {
-            return self.get_surrounding(pos.x, pos.y, PositionInNumber::Single);
-        }
-
-        let mut surrounding = vec![];
-        surrounding.extend(self.get_surrounding(pos.x, pos.y, PositionInNumber::Start));
-        surrounding.extend(self.get_surrounding(
-            pos.x + val.len() - 1,
-            pos.y,
-            PositionInNumber::End,
-        ));
-        for i in 1..val.len() - 1 {
-            surrounding.extend(self.get_surrounding(pos.x + i, pos.y, PositionInNumber::Middle));
-        }
+    /// Each gear that touches exactly as many numbers as the rules ask, with
+    /// those numbers.
+    fn gear_symbols(&self) -> impl Iterator<Item = &(Symbol, Vec<usize>)> + '_ {
+        self.index
+            .symbols
+            .iter()
+            .filter(|(symbol, numbers)| self.is_gear(symbol, numbers))
+    }
 
-        surrounding
+    fn is_gear(&self, symbol: &Symbol, numbers: &[usize]) -> bool {
+        symbol.c == self.rules.gear && numbers.len() == self.rules.gear_arity
     }
 
-    fn test_around_number(&self, x: usize, y: usize, val: &String) -> bool {
-        let pos = Pos { x, y };
-        self.get_characters_surrounding_number(&pos, val)
+    /// The sum of the part numbers, or `None` if it does not fit in a `usize`.
+    pub fn sum_valid_nums(&self) -> Option<usize> {
+        self.part_flags()
             .iter()
-            .any(|c| !c.c.is_digit(10) && c.c != '.')
-    }
-
-    fn find_gear_around_number(
-        &self,
-        gears: &mut HashMap<Pos, Vec<String>>,
-        pos: &Pos,
-        val: &String,
-    ) {
-        for c_pos in self.get_characters_surrounding_number(pos, val) {
-            if c_pos.c == '*' {
-                self.add_gear(gears, &c_pos.pos, val);
-            }
-        }
+            .zip(&self.index.numbers)
+            .filter(|(&is_part, _)| is_part)
+            .try_fold(0usize, |sum, (_, span)| sum.checked_add(span.value))
     }
 
-    fn add_gear(&self, gears: &mut HashMap<Pos, Vec<String>>, pos: &Pos, val: &String) {
-        match gears.contains_key(&pos) {
-            true => {
-                gears.get_mut(&pos).unwrap().push(val.clone());
-            }
-            false => {
-                gears.insert(pos.clone(), vec![val.clone()]);
+    fn part_flags(&self) -> Vec<bool> {
+        let mut is_part = vec![false; self.index.numbers.len()];
+        for (_, numbers) in &self.index.symbols {
+            for &n in numbers {
+                is_part[n] = true;
             }
         }
+        is_part
     }
 
-    fn get_coord(&self, x: usize, y: usize, dir: Direction) -> Result<CharPos, OutOfBoundsError> {
-        let mut x: i128 = x.try_into().unwrap();
-        let mut y: i128 = y.try_into().unwrap();
+    /// Draws the schematic with part numbers, other numbers, symbols and gears
+    /// told apart by ANSI colours, or left plain when `colour` is false. Each row
+    /// with gears ends with their ratios.
+    pub fn render(&self, colour: bool) -> String {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Style {
+            Empty,
+            Part,
+            Loose,
+            Symbol,
+            Gear,
+        }
+        const RESET: &str = "\x1b[0m";
+        let code = |style: Style| match style {
+            Style::Empty => RESET,
+            Style::Part => "\x1b[32m",
+            Style::Loose => "\x1b[31m",
+            Style::Symbol => "\x1b[33m",
+            Style::Gear => "\x1b[1;35m",
+        };
 
-        match dir {
-            Direction::LUp => {
-                x -= 1;
-                y -= 1
+        let mut styles = Grid::new(
+            self.schematic.width(),
+            self.schematic.height(),
+            Style::Empty,
+        );
+        for (span, is_part) in self.index.numbers.iter().zip(self.part_flags()) {
+            for x in span.columns.clone() {
+                styles[Pos { x, y: span.row }] = if is_part { Style::Part } else { Style::Loose };
             }
-            Direction::Up => y -= 1,
-            Direction::RUp => {
-                x += 1;
-                y -= 1
+        }
+        let mut ratios: Vec<Vec<String>> = vec![vec![]; self.schematic.height()];
+        for (symbol, _) in &self.index.symbols {
+            styles[symbol.pos] = Style::Symbol;
+        }
+        for (symbol, numbers) in self.gear_symbols() {
+            styles[symbol.pos] = Style::Gear;
+            let ratio = self.ratio(numbers).map_or(TOO_LARGE.to_string(), |r| r.to_string());
+            ratios[symbol.pos.y].push(ratio);
+        }
+
+        let mut out = String::new();
+        for (y, row) in self.schematic.rows().enumerate() {
+            let mut current = Style::Empty;
+            for (x, &c) in row.iter().enumerate() {
+                let style = styles[Pos { x, y }];
+                if colour && style != current {
+                    out.push_str(code(style));
+                    current = style;
+                }
+                out.push(c);
             }
-            Direction::L => x -= 1,
-            Direction::R => x += 1,
-            Direction::LDown => {
-                x -= 1;
-                y += 1
+            if colour && current != Style::Empty {
+                out.push_str(RESET);
             }
-            Direction::RDown => {
-                x += 1;
-                y += 1
+            if !ratios[y].is_empty() {
+                let _ = write!(out, "  gear ratio {}", ratios[y].join(", "));
             }
-            Direction::Down => y += 1,
-            Direction::Current => (),
+            out.push('\n');
         }
+        out
+    }
 
-        if x < 0 || y < 0 {
-            return Err(OutOfBoundsError);
+    /// The schematic as a Graphviz graph: a node for each number and symbol,
+    /// placed at its position, and an edge for each symbol touching a number.
+    pub fn graph_dot(&self) -> String {
+        let (parts, gears) = (self.part_flags(), self.gear_flags());
+        let mut out = String::from("graph schematic {\n  node [shape=box];\n");
+        for (n, span) in self.index.numbers.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  n{} [label={}, row={}, start={}, end={}, part={}, pos=\"{},{}!\"];",
+                n,
+                json_string(&span.value.to_string()),
+                span.row,
+                span.columns.start,
+                span.columns.end,
+                parts[n],
+                span.columns.start,
+                -(span.row as isize),
+            );
         }
+        for (s, (symbol, _)) in self.index.symbols.iter().enumerate() {
+            // JSON escapes are also valid in DOT strings
+            let _ = writeln!(
+                out,
+                "  s{} [label={}, shape=circle, row={}, column={}, gear={}, pos=\"{},{}!\"];",
+                s,
+                json_string(&symbol.c.to_string()),
+                symbol.pos.y,
+                symbol.pos.x,
+                gears[s],
+                symbol.pos.x,
+                -(symbol.pos.y as isize),
+            );
+        }
+        for (s, (_, numbers)) in self.index.symbols.iter().enumerate() {
+            for n in numbers {
+                let _ = writeln!(out, "  s{} -- n{};", s, n);
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// The same graph as [`Engine::graph_dot`], as JSON lists of nodes and edges.
+    pub fn graph_json(&self) -> String {
+        let (parts, gears) = (self.part_flags(), self.gear_flags());
+        let mut nodes: Vec<String> = self
+            .index
+            .numbers
+            .iter()
+            .enumerate()
+            .map(|(n, span)| {
+                format!(
+                    "    {{\"id\": \"n{}\", \"kind\": \"number\", \"value\": {}, \"row\": {}, \"start\": {}, \"end\": {}, \"part\": {}}}",
+                    n, span.value, span.row, span.columns.start, span.columns.end, parts[n]
+                )
+            })
+            .collect();
+        nodes.extend(self.index.symbols.iter().enumerate().map(|(s, (symbol, _))| {
+            format!(
+                "    {{\"id\": \"s{}\", \"kind\": \"symbol\", \"symbol\": {}, \"row\": {}, \"column\": {}, \"gear\": {}}}",
+                s,
+                json_string(&symbol.c.to_string()),
+                symbol.pos.y,
+                symbol.pos.x,
+                gears[s]
+            )
+        }));
+        let edges: Vec<String> = self
+            .index
+            .symbols
+            .iter()
+            .enumerate()
+            .flat_map(|(s, (_, numbers))| {
+                numbers
+                    .iter()
+                    .map(move |n| format!("    {{\"from\": \"s{}\", \"to\": \"n{}\"}}", s, n))
+            })
+            .collect();
+        format!(
+            "{{\n  \"nodes\": [\n{}\n  ],\n  \"edges\": [\n{}\n  ]\n}}\n",
+            nodes.join(",\n"),
+            edges.join(",\n")
+        )
+    }
 
-        let x: usize = x.unsigned_abs().try_into().unwrap();
-        let y: usize = y.unsigned_abs().try_into().unwrap();
+    /// Whether each symbol, in index order, is a gear under the rules.
+    fn gear_flags(&self) -> Vec<bool> {
+        self.index
+            .symbols
+            .iter()
+            .map(|(symbol, numbers)| self.is_gear(symbol, numbers))
+            .collect()
+    }
 
-        let row = match self.schematic.get(y) {
-            Some(val) => val,
-            None => return Err(OutOfBoundsError),
-        };
+    pub fn with_rules(mut self, rules: Rules) -> Engine {
+        self.index.relink(&self.schematic, &rules);
+        self.rules = rules;
+        self
+    }
 
-        match row.get(x) {
-            Some(val) => Ok(CharPos {
-                c: val.clone(),
-                pos: Pos { x, y },
-            }),
-            None => Err(OutOfBoundsError),
-        }
+    pub fn rules(&self) -> &Rules {
+        &self.rules
     }
 
-    fn get_surrounding(
-        &self,
-        x: usize,
-        y: usize,
-        position_in_number: PositionInNumber,
-    ) -> Vec<CharPos> {
-        let mut surrounding = vec![];
-        match position_in_number {
-            PositionInNumber::Start => {
-                surrounding.push(self.get_coord(x, y, Direction::L).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::LUp).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::Up).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::LDown).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::Down).unwrap_or_default());
-            }
-            PositionInNumber::Middle => {
-                surrounding.push(self.get_coord(x, y, Direction::Up).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::Down).unwrap_or_default());
-            }
-            PositionInNumber::End => {
-                surrounding.push(self.get_coord(x, y, Direction::R).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::RUp).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::Up).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::RDown).unwrap_or_default());
-                surrounding.push(self.get_coord(x, y, Direction::Down).unwrap_or_default());
-            }
-            PositionInNumber::Single => {
-                surrounding = Direction::iter()
-                    .filter(|dir| dir != &Direction::Current)
-                    .map(|dir| {
-                        self.get_coord(x, y, dir).unwrap_or(CharPos {
-                            c: '.'.to_string().chars().next().unwrap(),
-                            pos: Pos { x: 0, y: 0 },
-                        })
-                    })
-                    .collect()
+    /// Every number in the schematic, row by row.
+    pub fn numbers(&self) -> Vec<SchematicNumber> {
+        let mut numbers: Vec<SchematicNumber> = self
+            .index
+            .numbers
+            .iter()
+            .map(|span| SchematicNumber {
+                value: span.value,
+                row: span.row,
+                columns: span.columns.clone(),
+                symbols: vec![],
+            })
+            .collect();
+        // symbols are indexed in reading order, so each list comes out sorted
+        for (symbol, touching) in &self.index.symbols {
+            for &n in touching {
+                numbers[n].symbols.push(*symbol);
             }
         }
-        surrounding
+        numbers
+    }
+
+    pub fn from_string(input: String) -> Result<Engine, ParseError> {
+        Engine::from_lines(input.lines())
+    }
+
+    /// Reads a schematic whose rows must all be the same width. Windows line
+    /// endings and blank lines at the end are ignored.
+    pub fn from_lines<I, S>(lines: I) -> Result<Engine, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let lines = clean_lines(lines);
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        for (i, line) in lines.iter().enumerate() {
+            let found = match line.char_indices().nth(width) {
+                // too long: point at the first extra cell
+                Some((extra, _)) => &line[extra..],
+                None if line.chars().count() < width => &line[line.len()..],
+                None => continue,
+            };
+            let expected = format!("a row of {} cells like the first", width);
+            return Err(ParseError::new(line, found, expected).on_line(i + 1, line));
+        }
+        Engine::build(lines, '.', Rules::default())
+    }
+
+    /// Reads a schematic that may have rows of different widths, filling short
+    /// rows with the rules' empty cell. Also returns the 1-based numbers of the
+    /// rows that were padded.
+    pub fn from_ragged_lines<I, S>(
+        lines: I,
+        rules: Rules,
+    ) -> Result<(Engine, Vec<usize>), ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let lines = clean_lines(lines);
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let padded = (0..lines.len())
+            .filter(|&i| lines[i].chars().count() < width)
+            .map(|i| i + 1)
+            .collect();
+        Ok((Engine::build(lines, rules.empty, rules)?, padded))
+    }
+
+    fn build(lines: Vec<String>, empty: char, rules: Rules) -> Result<Engine, ParseError> {
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        let rows = lines
+            .iter()
+            .map(|line| {
+                let mut row: Vec<char> = line.chars().collect();
+                row.resize(width, empty);
+                row
+            })
+            .collect();
+        let schematic = Grid::from_rows(rows).expect("rows were padded to the same width");
+        Ok(Engine {
+            index: Index::build(&schematic, &rules)?,
+            schematic,
+            rules,
+        })
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// The lines without carriage returns, and without blank lines at the end.
+fn clean_lines<I, S>(lines: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut lines: Vec<String> = lines
+        .into_iter()
+        .map(|line| {
+            let line = line.as_ref();
+            line.strip_suffix('\r').unwrap_or(line).to_string()
+        })
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
 
-    fn gen_engine() -> Engine {
-        let input = "\
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "schematic",
+        input: "\
 467..114..
 ...*......
 ..35..633.
@@ -315,76 +552,282 @@ mod tests {
 ......755.
 ...$.*....
 .664.598..
-........50"
-            .to_string();
-        let engine = Engine::from_string(input);
-        engine
+........50",
+        part1: Some("4361"),
+        part2: Some("467835"),
+    },
+    Example {
+        name: "gear on the border",
+        input: "\
+.2.
+.*.
+585
+",
+        part1: Some("587"),
+        part2: Some("1170"),
+    },
+];
+
+impl Solution for Engine {
+    fn parse_lines<I, S>(lines: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Engine::from_lines(lines)
+    }
+
+    fn part1(&self) -> Answer {
+        checked_answer(self.sum_valid_nums())
+    }
+
+    fn part2(&self) -> Answer {
+        checked_answer(self.sum_gear_ratios())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gen_engine() -> Engine {
+        Engine::from_string(EXAMPLES[0].input.to_string()).unwrap()
     }
 
     #[test]
     fn sum_gear_ratios_with_edge_cases_should_work() {
-        let input = "\
-.2.
-.*.
-585
-"
-        .to_string();
-        let engine = Engine::from_string(input);
-        assert_eq!(engine.sum_gear_ratios(), 1170);
+        let engine = Engine::from_string(EXAMPLES[1].input.to_string()).unwrap();
+        assert_eq!(engine.sum_gear_ratios(), Some(1170));
     }
     #[test]
     fn sum_gear_ratios_should_work() {
         let engine = gen_engine();
-        assert_eq!(engine.sum_gear_ratios(), 467835);
+        assert_eq!(engine.sum_gear_ratios(), Some(467835));
     }
 
     #[test]
-    fn find_gears_should_work() {
+    fn index_should_link_symbols_to_numbers() {
         let engine = gen_engine();
-        let gears = engine.find_gears();
-        dbg!(gears);
+        let (gear, touching) = &engine.index.symbols[0];
+        assert_eq!(gear.pos, Pos::new(3, 1));
+        let values: Vec<usize> = touching
+            .iter()
+            .map(|&n| engine.index.numbers[n].value)
+            .collect();
+        assert_eq!(values, vec![467, 35]);
+        assert_eq!(engine.index.symbols.len(), 6);
+        assert_eq!(engine.index.numbers[1].columns, 5..8);
+    }
+
+    #[test]
+    fn numbers_should_list_adjacent_symbols() {
+        let numbers = gen_engine().numbers();
+        assert_eq!(numbers.len(), 11);
+        assert_eq!(
+            numbers[0],
+            SchematicNumber {
+                value: 467,
+                row: 0,
+                columns: 0..3,
+                symbols: vec![Symbol {
+                    c: '*',
+                    pos: Pos::new(3, 1)
+                }],
+            }
+        );
+        assert_eq!(numbers[1].value, 114);
+        assert!(!numbers[1].is_part());
+        let parts: usize = numbers
+            .iter()
+            .filter(|n| n.is_part())
+            .map(|n| n.value)
+            .sum();
+        assert_eq!(parts, 4361);
+
+        let border = Engine::from_string(EXAMPLES[1].input.to_string())
+            .unwrap()
+            .numbers();
+        assert_eq!(border[1].columns, 0..3);
+        assert_eq!(border[1].symbols[0].pos, Pos::new(1, 1));
+    }
+
+    #[test]
+    fn rules_should_change_what_counts() {
+        let four_way = gen_engine().with_rules(Rules {
+            adjacency: Adjacency::Four,
+            ..Rules::default()
+        });
+        // 467 only touches its gear diagonally
+        assert!(!four_way.numbers()[0].is_part());
+        assert_eq!(four_way.sum_gear_ratios(), Some(0));
+
+        let hashes_only = gen_engine().with_rules(Rules {
+            symbols: Symbols::Only(vec!['#']),
+            ..Rules::default()
+        });
+        assert_eq!(hashes_only.sum_valid_nums(), Some(633));
+        // '*' is no longer a symbol, so it is no longer a gear either
+        assert_eq!(hashes_only.sum_gear_ratios(), Some(0));
+        let hashes_and_gears = gen_engine().with_rules(Rules {
+            symbols: Symbols::Only(vec!['#', '*']),
+            ..Rules::default()
+        });
+        assert_eq!(hashes_and_gears.sum_gear_ratios(), Some(467835));
+
+        let lone_gears = gen_engine().with_rules(Rules {
+            gear_arity: 1,
+            ..Rules::default()
+        });
+        assert_eq!(lone_gears.sum_gear_ratios(), Some(617));
+
+        let engine = Engine::from_lines(["_12_", "_#__", "3___"])
+            .unwrap()
+            .with_rules(Rules {
+                empty: '_',
+                gear: '#',
+                gear_arity: 3,
+                ..Rules::default()
+            });
+        assert_eq!(engine.sum_valid_nums(), Some(15));
+        assert_eq!(engine.sum_gear_ratios(), Some(0));
     }
 
     #[test]
     fn part_1_should_work() {
         let engine = gen_engine();
         let sum = engine.sum_valid_nums();
-        assert_eq!(sum, 4361);
+        assert_eq!(sum, Some(4361));
     }
 
     #[test]
-    fn test_surrounding_should_work() {
-        let engine = gen_engine();
-        assert_eq!(engine.test_around_number(5, 0, &"114".to_string()), false);
-        assert_eq!(engine.test_around_number(2, 2, &"35".to_string()), true);
+    fn numbers_should_only_count_once_per_symbol() {
+        // 123 touches the * through all three of its digits
+        let engine = Engine::from_lines(["123", ".*.", "4.."]).unwrap();
+        assert_eq!(engine.index.symbols[0].1, vec![0, 1]);
+        assert_eq!(engine.sum_gear_ratios(), Some(492));
+        assert_eq!(engine.sum_valid_nums(), Some(127));
     }
 
     #[test]
-    fn numbers_from_line_should_work() {
-        let line = "467..114..".chars().collect();
-        let numbs = parse_line_into_numbers(&line);
-        assert_eq!(numbs.len(), 2);
-        assert_eq!(numbs[0].value, "467");
-        assert_eq!(numbs[1].x, 5);
+    fn numbers_should_end_at_the_row_end() {
+        let engine = Engine::from_lines(["..12", "34.#"]).unwrap();
+        let values: Vec<(usize, Range<usize>)> = engine
+            .numbers()
+            .into_iter()
+            .map(|n| (n.value, n.columns))
+            .collect();
+        assert_eq!(values, vec![(12, 2..4), (34, 0..2)]);
+        assert_eq!(engine.sum_valid_nums(), Some(12));
     }
 
     #[test]
-    fn get_surrounding_should_work() {
-        let engine = gen_engine();
+    fn render_should_highlight_each_kind_of_cell() {
+        let engine = Engine::from_string(EXAMPLES[1].input.to_string()).unwrap();
+        assert_eq!(engine.render(false), ".2.\n.*.  gear ratio 1170\n585\n");
+
+        let engine = Engine::from_lines(["1.#", "...", "..3"]).unwrap();
         assert_eq!(
-            engine
-                .get_surrounding(2, 2, PositionInNumber::Single)
-                .into_iter()
-                .map(|c_pos| c_pos.c)
-                .collect::<Vec<char>>(),
-            vec!['.', '.', '*', '.', '5', '.', '.', '.']
-        )
+            engine.render(true),
+            "\x1b[31m1\x1b[0m.\x1b[33m#\x1b[0m\n...\n..\x1b[31m3\x1b[0m\n"
+        );
+        let engine = Engine::from_lines([".2.", ".*.", "585"]).unwrap();
+        assert!(engine
+            .render(true)
+            .starts_with(".\x1b[32m2\x1b[0m.\n.\x1b[1;35m*\x1b[0m.  gear ratio 1170\n"));
     }
 
     #[test]
-    fn get_coord_should_work() {
-        let engine = gen_engine();
-        assert_eq!(engine.get_coord(1, 0, Direction::Current).unwrap().c, '6');
-        assert_eq!(engine.get_coord(1, 0, Direction::R).unwrap().c, '7')
+    fn ragged_rows_should_be_rejected() {
+        let err = Engine::from_lines(["123", "4", "..."]).err().unwrap();
+        assert_eq!(err.line, Some(2));
+        assert_eq!(err.column, 2);
+        assert_eq!(err.expected, "a row of 3 cells like the first");
+
+        let err = Engine::from_lines(["12", "3*4"]).err().unwrap();
+        assert_eq!((err.line, err.column), (Some(2), 3));
+        assert_eq!(err.found, "4");
+    }
+
+    #[test]
+    fn ragged_rows_can_be_padded() {
+        let rules = Rules {
+            empty: '_',
+            ..Rules::default()
+        };
+        let (engine, padded) = Engine::from_ragged_lines(["1*", "2", "345", ""], rules).unwrap();
+        assert_eq!(padded, vec![1, 2]);
+        assert_eq!(engine.render(false), "1*_  gear ratio 2\n2__\n345\n");
+        assert_eq!(engine.sum_valid_nums(), Some(3));
+    }
+
+    #[test]
+    fn crlf_and_trailing_blank_lines_should_not_change_answers() {
+        let crlf = EXAMPLES[0].input.replace('\n', "\r\n") + "\r\n\r\n\n";
+        let engine = Engine::from_string(crlf).unwrap();
+        assert_eq!(engine.sum_valid_nums(), Some(4361));
+        assert_eq!(engine.sum_gear_ratios(), Some(467835));
+        assert_eq!(engine.numbers().len(), 11);
+    }
+
+    #[test]
+    fn graph_should_link_symbols_to_the_numbers_they_touch() {
+        let engine = Engine::from_lines([".2.", ".*.", "585", "#.."]).unwrap();
+        let dot = engine.graph_dot();
+        assert!(dot.starts_with("graph schematic {\n"));
+        assert!(
+            dot.contains("  n0 [label=\"2\", row=0, start=1, end=2, part=true, pos=\"1,0!\"];\n")
+        );
+        assert!(dot.contains("  s0 [label=\"*\", shape=circle, row=1, column=1, gear=true,"));
+        assert!(dot.contains("  s1 [label=\"#\", shape=circle, row=3, column=0, gear=false,"));
+        assert_eq!(dot.matches(" -- ").count(), 3);
+        assert!(dot.ends_with("  s0 -- n0;\n  s0 -- n1;\n  s1 -- n1;\n}\n"));
+    }
+
+    #[test]
+    fn graph_json_should_parse() {
+        let engine = Engine::from_string(EXAMPLES[0].input.to_string()).unwrap();
+        let graph: serde_json::Value = serde_json::from_str(&engine.graph_json()).unwrap();
+        let nodes = graph["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 11 + 6);
+        assert_eq!(nodes[0]["value"], 467);
+        assert_eq!(nodes[11]["symbol"], "*");
+        assert_eq!(nodes[11]["gear"], true);
+        let edges = graph["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 8);
+        assert_eq!(edges[0]["from"], "s0");
+        assert_eq!(edges[0]["to"], "n0");
+    }
+
+    #[test]
+    fn numbers_too_big_to_count_should_be_rejected() {
+        let long = "9".repeat(25);
+        let row = format!("é*{}", long);
+        let err = Engine::from_lines([".".repeat(27), row]).err().unwrap();
+        // the column is in bytes, and 'é' takes two
+        assert_eq!((err.line, err.column), (Some(2), 4));
+        assert_eq!(err.found, long);
+
+        let err = Engine::from_ragged_lines(["*", &long], Rules::default())
+            .err()
+            .unwrap();
+        assert_eq!((err.line, err.column), (Some(2), 1));
+
+        let engine = Engine::from_lines([usize::MAX.to_string()]).unwrap();
+        assert_eq!(engine.numbers()[0].value, usize::MAX);
+    }
+
+    #[test]
+    fn totals_too_big_to_count_should_be_none() {
+        let big = "99999999999";
+        let engine = Engine::from_lines([format!("{}*{}", big, big)]).unwrap();
+        assert_eq!(engine.sum_gear_ratios(), None);
+        assert_eq!(engine.part2().to_string(), TOO_LARGE);
+        assert!(engine.render(false).ends_with(&format!("  gear ratio {}\n", TOO_LARGE)));
+        assert_eq!(engine.sum_valid_nums(), Some(199999999998));
+
+        let engine = Engine::from_lines([format!("{}*1", usize::MAX)]).unwrap();
+        assert_eq!(engine.sum_valid_nums(), None);
+        assert_eq!(engine.part1().to_string(), TOO_LARGE);
+        assert_eq!(engine.sum_gear_ratios(), Some(usize::MAX));
     }
 }
@@ -1,5 +1,8 @@
 use std::collections::BTreeMap;
 
+use crate::error::ParseError;
+use crate::solution::{checked_answer, Answer, Example, Solution};
+
 #[derive(Clone, Debug, PartialEq)]
 struct Card {
     id: usize,
@@ -8,30 +11,66 @@ struct Card {
 }
 
 trait StringSliceHelpers {
-    fn parse_numbers_from_space_separated(&self) -> Vec<u32>;
+    fn parse_numbers_from_space_separated(&self) -> Result<Vec<u32>, ParseError>;
 }
 
 impl StringSliceHelpers for str {
-    fn parse_numbers_from_space_separated(&self) -> Vec<u32> {
+    fn parse_numbers_from_space_separated(&self) -> Result<Vec<u32>, ParseError> {
         self.split(" ")
             .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>()
+            .map(|s| {
+                s.parse::<u32>()
+                    .map_err(|_| ParseError::new(self, s, "a number"))
+            })
+            .collect::<Result<Vec<u32>, ParseError>>()
     }
 }
 
-pub fn sum_all_cards(lines: &Vec<String>) -> u32 {
-    let mut sum = 0;
-    for line in lines {
-        let card = Card::from_line(&line);
-        sum += card.calculate_score();
+pub fn sum_all_cards<I, S>(lines: I) -> Result<Option<u64>, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let cards = parse_cards(lines)?;
+    Ok(total_score(&cards))
+}
+
+/// Parses every card, checking that the ids count up from 1 like the puzzle's,
+/// since cards win copies of the cards after them by id.
+fn parse_cards<I, S>(lines: I) -> Result<Vec<Card>, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.as_ref();
+            let card = Card::from_line(line).map_err(|e| e.on_line(i + 1, line))?;
+            if card.id != i + 1 {
+                let expected = format!("card id {}", i + 1);
+                return Err(
+                    ParseError::new(line, Card::id_text(line), expected).on_line(i + 1, line)
+                );
+            }
+            Ok(card)
+        })
+        .collect()
+}
+
+/// The sum of the cards' scores, or `None` if it does not fit in a `u64`.
+fn total_score(cards: &[Card]) -> Option<u64> {
+    let mut sum: u64 = 0;
+    for card in cards {
+        sum = sum.checked_add(card.calculate_score()?)?;
     }
-    sum
+    Some(sum)
 }
 
 #[derive(Clone, Debug, PartialEq)]
 struct NumberOfCards {
-    num_cards: u32,
+    num_cards: u64,
     card: Card,
 }
 
@@ -41,56 +80,122 @@ impl NumberOfCards {
     }
 }
 
-pub fn sum_all_cards_with_new_rules(lines: &Vec<String>) -> u32 {
-    let mut cards = BTreeMap::new();
-    for line in lines {
-        let card = Card::from_line(&line);
-        cards.insert(card.id, NumberOfCards::new(card));
-    }
+pub fn sum_all_cards_with_new_rules<I, S>(lines: I) -> Result<Option<u64>, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let cards = parse_cards(lines)?;
+    Ok(count_won_cards(&cards))
+}
 
-    let mut sum = 0;
+/// How many cards there are once every win is copied, or `None` if that does
+/// not fit in a `u64`. The copies can grow exponentially.
+fn count_won_cards(cards: &[Card]) -> Option<u64> {
+    let mut cards: BTreeMap<usize, NumberOfCards> = cards
+        .iter()
+        .map(|card| (card.id, NumberOfCards::new(card.clone())))
+        .collect();
+
+    let mut sum: u64 = 0;
 
     // cards start at index 1
     // can't use an iterator here, because we need to both grab a card and mutate others
     for index in 1..=cards.len() {
-        let card_pile = cards.remove(&index).unwrap();
-        let num_wins = card_pile.card.calculate_number_of_wins();
-        if num_wins > 0 {
-            for i in 1..=num_wins {
-                let future_card_pile = cards.get_mut(&(index + i as usize)).unwrap();
-                future_card_pile.num_cards += card_pile.num_cards;
-            }
+        let Some(card_pile) = cards.remove(&index) else {
+            continue;
+        };
+        let num_wins = card_pile.card.calculate_number_of_wins() as usize;
+        // wins past the last card have nothing to copy
+        for future_card_pile in cards
+            .range_mut(index + 1..index + 1 + num_wins)
+            .map(|(_, pile)| pile)
+        {
+            future_card_pile.num_cards = future_card_pile
+                .num_cards
+                .checked_add(card_pile.num_cards)?;
         }
-        sum += card_pile.num_cards;
+        sum = sum.checked_add(card_pile.num_cards)?;
     }
 
-    sum
+    Some(sum)
+}
+
+pub const EXAMPLES: &[Example] = &[Example {
+    name: "scratchcards",
+    input: "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    part1: Some("13"),
+    part2: Some("30"),
+}];
+
+pub struct Day4 {
+    cards: Vec<Card>,
+}
+
+impl Solution for Day4 {
+    fn parse_lines<I, S>(lines: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Ok(Day4 {
+            cards: parse_cards(lines)?,
+        })
+    }
+
+    fn part1(&self) -> Answer {
+        checked_answer(total_score(&self.cards))
+    }
+
+    fn part2(&self) -> Answer {
+        checked_answer(count_won_cards(&self.cards))
+    }
 }
 
 impl Card {
-    fn from_line(line: &str) -> Card {
+    fn from_line(line: &str) -> Result<Card, ParseError> {
         //                         Winning       Picks
         // example line: Card 1: 32 48 49 10 | 83 4 32 1 10
-        let parts = line
-            .split(":")
-            .filter(|l| !l.is_empty())
-            .collect::<Vec<&str>>();
-        let id = parts[0]
-            .trim()
-            .split(" ")
-            .filter(|l| !l.is_empty())
-            .collect::<Vec<&str>>()[1]
+        let (header, numbers) = line
+            .split_once(":")
+            .ok_or_else(|| ParseError::new(line, &line[line.len()..], "':' after the card id"))?;
+        if !header.trim().starts_with("Card") {
+            return Err(ParseError::new(line, header.trim(), "\"Card\""));
+        }
+        let id_text = Card::id_text(line);
+        let id = id_text
             .parse::<usize>()
-            .unwrap();
+            .map_err(|_| ParseError::new(line, id_text, "a card id"))?;
 
-        let numbers = parts[1];
+        let (winning, picks) = numbers.split_once("|").ok_or_else(|| {
+            ParseError::new(
+                line,
+                &line[line.len()..],
+                "'|' between winning numbers and picks",
+            )
+        })?;
+        let winning = winning
+            .parse_numbers_from_space_separated()
+            .map_err(|e| e.inside(line, winning))?;
+        let picks = picks
+            .parse_numbers_from_space_separated()
+            .map_err(|e| e.inside(line, picks))?;
 
-        let winning =
-            numbers.split("|").collect::<Vec<&str>>()[0].parse_numbers_from_space_separated();
-        let picks =
-            numbers.split("|").collect::<Vec<&str>>()[1].parse_numbers_from_space_separated();
+        Ok(Card { id, winning, picks })
+    }
 
-        Card { id, winning, picks }
+    /// The id part of a line's "Card N:" header, or an empty slice at the end
+    /// of the header if there is no id.
+    fn id_text(line: &str) -> &str {
+        let header = line.split_once(":").map_or(line, |(header, _)| header);
+        let header = header.trim();
+        header.strip_prefix("Card").unwrap_or(header).trim()
     }
 
     fn calculate_number_of_wins(&self) -> u32 {
@@ -102,27 +207,29 @@ impl Card {
         }
         wins
     }
-    fn calculate_score(&self) -> u32 {
-        let mut score = 0;
+    /// `None` if the card has so many wins that its score does not fit in a `u64`.
+    fn calculate_score(&self) -> Option<u64> {
+        let mut score: u64 = 0;
         for num in self.winning.iter() {
             if self.picks.contains(num) {
                 match score {
                     0 => score = 1,
-                    _ => score *= 2,
+                    _ => score = score.checked_mul(2)?,
                 }
             }
         }
-        score
+        Some(score)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solution::TOO_LARGE;
     #[test]
     fn test_card_from_line() {
         let line = "Card 1: 32 48 49 10 | 83 4 32 1 10";
-        let card = Card::from_line(line);
+        let card = Card::from_line(line).unwrap();
         assert_eq!(card.winning, vec![32, 48, 49, 10]);
         assert_eq!(card.picks, vec![83, 4, 32, 1, 10]);
         assert_eq!(card.id, 1);
@@ -135,39 +242,79 @@ mod tests {
             picks: vec![83, 4, 32, 1, 10],
             id: 1,
         };
-        assert_eq!(card.calculate_score(), 2);
+        assert_eq!(card.calculate_score(), Some(2));
     }
 
     #[test]
     fn test_sum_all_cards() {
-        let input = "\
-Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-
-        let lines = input
+        let lines = EXAMPLES[0]
+            .input
             .split("\n")
-            .map(|s| String::from(s))
+            .map(String::from)
             .collect::<Vec<String>>();
-        assert_eq!(sum_all_cards(&lines), 13);
+        assert_eq!(sum_all_cards(&lines).unwrap(), Some(13));
     }
 
     #[test]
     fn test_sum_all_cards_with_new_rules() {
-        let input = "\
-Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
-        let lines = input
+        let lines = EXAMPLES[0]
+            .input
             .split("\n")
-            .map(|s| String::from(s))
+            .map(String::from)
             .collect::<Vec<String>>();
-        assert_eq!(sum_all_cards_with_new_rules(&lines), 30);
+        assert_eq!(sum_all_cards_with_new_rules(&lines).unwrap(), Some(30));
+    }
+
+    #[test]
+    fn test_card_from_line_reports_bad_number() {
+        let err = Card::from_line("Card 1: 32 4x | 83 4").unwrap_err();
+        assert_eq!(err.column, 12);
+        assert_eq!(err.found, "4x");
+        assert_eq!(err.expected, "a number");
+    }
+
+    #[test]
+    fn test_card_ids_must_count_up_from_one() {
+        let err = Day4::parse_lines(["Card 2: 1 2 | 1 3", "Card 3: 4 | 5"])
+            .err()
+            .unwrap();
+        assert_eq!(err.line, Some(1));
+        assert_eq!(err.column, 6);
+        assert_eq!(err.found, "2");
+        assert_eq!(err.expected, "card id 1");
+
+        let err = Day4::parse_lines(["Card 1: 4 | 5", "Card  1: 4 | 5"])
+            .err()
+            .unwrap();
+        assert_eq!((err.line, err.column), (Some(2), 7));
+    }
+
+    #[test]
+    fn test_wins_past_the_last_card_are_ignored() {
+        let cards = Day4::parse_lines(["Card 1: 1 | 1"]).unwrap();
+        assert_eq!(cards.part1().to_string(), "1");
+        assert_eq!(cards.part2().to_string(), "1");
+
+        let cards = Day4::parse_lines(["Card 1: 1 2 | 1 2", "Card 2: 3 | 3"]).unwrap();
+        assert_eq!(cards.part2().to_string(), "3");
+    }
+
+    #[test]
+    fn test_totals_too_big_to_count() {
+        let card = |id: usize, wins: u32| {
+            let numbers: Vec<String> = (1..=wins).map(|n| n.to_string()).collect();
+            let numbers = numbers.join(" ");
+            format!("Card {}: {} | {}", id, numbers, numbers)
+        };
+        let cards = Day4::parse_lines([card(1, 40)]).unwrap();
+        assert_eq!(cards.part1().to_string(), (1u64 << 39).to_string());
+        let cards = Day4::parse_lines([card(1, 70)]).unwrap();
+        assert_eq!(cards.part1().to_string(), TOO_LARGE);
+
+        // every card wins the next five, so the copies keep growing
+        let cards = Day4::parse_lines((1..=100).map(|id| card(id, 5))).unwrap();
+        assert_eq!(cards.part2().to_string(), TOO_LARGE);
+        let cards = Day4::parse_lines((1..=40).map(|id| card(id, 5))).unwrap();
+        assert_ne!(cards.part2().to_string(), TOO_LARGE);
     }
 }
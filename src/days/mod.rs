@@ -1,4 +1,67 @@
-pub mod day_1;
-pub mod day_2;
-pub mod day_3;
-pub mod day_4;
+use crate::solution::Day;
+
+/// Declares each day's module and registers its solution and examples. Every
+/// registered day also gets a `part1` and `part2` test that checks its examples.
+macro_rules! register_days {
+    ($($module:ident: $day:literal => $solution:ident),* $(,)?) => {
+        $(pub mod $module;)*
+
+        /// Every implemented day, in order.
+        pub const DAYS: &[Day] = &[$(Day::new::<$module::$solution>($day, $module::EXAMPLES)),*];
+
+        #[cfg(test)]
+        mod example_tests {
+            $(
+                mod $module {
+                    #[test]
+                    fn part1() {
+                        crate::days::get($day).unwrap().check_examples(1).unwrap();
+                    }
+
+                    #[test]
+                    fn part2() {
+                        crate::days::get($day).unwrap().check_examples(2).unwrap();
+                    }
+                }
+            )*
+        }
+    };
+}
+
+register_days! {
+    day_1: 1 => Day1,
+    day_2: 2 => Day2,
+    day_3: 3 => Engine,
+    day_4: 4 => Day4,
+}
+
+pub fn get(day: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_should_be_unique_and_in_order() {
+        let days: Vec<u8> = DAYS.iter().map(|d| d.day).collect();
+        let mut sorted = days.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(days, sorted);
+    }
+
+    #[test]
+    fn get_should_find_registered_days() {
+        assert_eq!(get(3).unwrap().day, 3);
+        assert!(get(25).is_none());
+    }
+
+    #[test]
+    fn every_day_should_have_examples() {
+        for day in DAYS {
+            assert!(!day.examples.is_empty(), "day {} has no examples", day.day);
+        }
+    }
+}
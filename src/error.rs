@@ -0,0 +1,136 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+/// A problem found while parsing puzzle input.
+///
+/// `column` is the 1-based byte column within the line where the unexpected text
+/// starts. `line` is the 1-based line number, which is only known once the error has
+/// passed through something that reads whole inputs (see
+/// [`parse_lines`](crate::utilities::parse_lines)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+    text: Option<String>,
+}
+
+impl ParseError {
+    /// Builds an error for `found`, which must be a slice of `within`. This is
+    /// only checked in debug builds, so it stays inside the crate, where every
+    /// parser passes slices of the line it was given.
+    pub(crate) fn new(within: &str, found: &str, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            line: None,
+            column: offset_of(within, found) + 1,
+            expected: expected.into(),
+            found: found.to_string(),
+            text: None,
+        }
+    }
+
+    /// Re-bases the column of an error raised while parsing `inner`, a slice of `outer`.
+    pub(crate) fn inside(mut self, outer: &str, inner: &str) -> ParseError {
+        self.column += offset_of(outer, inner);
+        self
+    }
+
+    /// Records which line of the input the error came from.
+    pub fn on_line(mut self, number: usize, text: &str) -> ParseError {
+        self.line = Some(number);
+        self.text = Some(text.to_string());
+        self
+    }
+}
+
+/// Byte offset of `inner` within `outer`. Every parser here works on sub-slices of
+/// the line it was given, so the pointers tell us where the slice started.
+fn offset_of(outer: &str, inner: &str) -> usize {
+    let offset = (inner.as_ptr() as usize).wrapping_sub(outer.as_ptr() as usize);
+    debug_assert!(
+        offset <= outer.len(),
+        "{:?} is not a slice of {:?}",
+        inner,
+        outer
+    );
+    offset.min(outer.len())
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}, ", line)?;
+        }
+        write!(f, "column {}: expected {}, ", self.column, self.expected)?;
+        match self.found.as_str() {
+            "" => write!(f, "found end of line")?,
+            found => write!(f, "found {:?}", found)?,
+        }
+        if let Some(text) = &self.text {
+            write!(f, "\n  {}\n  {:>width$}", text, "^", width = self.column)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for ParseError {}
+
+/// Anything that can go wrong while reading and parsing an input.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "could not read input: {}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_should_point_at_found_text() {
+        let line = "Card 1: 1 2 x";
+        let err = ParseError::new(line, &line[12..], "a number");
+        assert_eq!(err.column, 13);
+        assert_eq!(err.found, "x");
+    }
+
+    #[test]
+    fn inside_should_rebase_column() {
+        let line = "Game 1: 3 blue; 2 purple";
+        let pull = &line[15..];
+        let err = ParseError::new(pull, &pull[3..], "a colour").inside(line, pull);
+        assert_eq!(err.column, 19);
+    }
+}
@@ -0,0 +1,331 @@
+//! Downloading puzzle inputs into the inputs directory.
+//!
+//! Inputs never change once published, so a day that already has its real input
+//! on disk is never requested again. Requests are spaced out by at least
+//! [`MIN_INTERVAL`], tracked across runs with a timestamp file in the inputs
+//! directory.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::inputs::{Inputs, REAL};
+
+pub const MIN_INTERVAL: Duration = Duration::from_secs(5);
+const LAST_FETCH_FILE: &str = ".last_fetch";
+const USER_AGENT: &str = "github.com/JosephBrooksbank/advent_of_code_2023 input fetcher";
+
+#[derive(Debug)]
+pub enum FetchError {
+    NoSession,
+    Status { url: String, status: u16 },
+    Transport { url: String, message: String },
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NoSession => {
+                write!(f, "No session token, set session in advent_of_code.toml")
+            }
+            FetchError::Status { url, status } => write!(f, "{} returned HTTP {}", url, status),
+            FetchError::Transport { url, message } => {
+                write!(f, "Could not reach {}: {}", url, message)
+            }
+            FetchError::Io { path, source } => {
+                write!(f, "Could not write {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// Something that can perform an authenticated GET request.
+pub trait HttpBackend {
+    fn get(&self, url: &str, session: &str) -> Result<String, FetchError>;
+}
+
+/// The real backend, over HTTP or HTTPS.
+pub struct UreqBackend {
+    agent: ureq::Agent,
+}
+
+impl UreqBackend {
+    pub fn new() -> UreqBackend {
+        UreqBackend {
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+}
+
+impl Default for UreqBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpBackend for UreqBackend {
+    fn get(&self, url: &str, session: &str) -> Result<String, FetchError> {
+        let response = self
+            .agent
+            .get(url)
+            .set("Cookie", &format!("session={}", session))
+            .call()
+            .map_err(|e| match e {
+                ureq::Error::Status(status, _) => FetchError::Status {
+                    url: url.to_string(),
+                    status,
+                },
+                // the transport error's own Display repeats the url
+                ureq::Error::Transport(t) => FetchError::Transport {
+                    url: url.to_string(),
+                    message: match t.message() {
+                        Some(message) => format!("{}: {}", t.kind(), message),
+                        None => t.kind().to_string(),
+                    },
+                },
+            })?;
+        response.into_string().map_err(|e| FetchError::Transport {
+            url: url.to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Fetched {
+    Downloaded(PathBuf),
+    AlreadyCached(PathBuf),
+}
+
+pub struct Fetcher<B: HttpBackend> {
+    backend: B,
+    base_url: String,
+    session: Option<String>,
+    min_interval: Duration,
+}
+
+impl<B: HttpBackend> Fetcher<B> {
+    pub fn new(backend: B, base_url: &str, session: Option<String>) -> Fetcher<B> {
+        Fetcher {
+            backend,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+            min_interval: MIN_INTERVAL,
+        }
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Fetcher<B> {
+        self.min_interval = min_interval;
+        self
+    }
+
+    pub fn url(&self, day: u8) -> String {
+        format!("{}/day/{}/input", self.base_url, day)
+    }
+
+    /// Downloads the real input for `day` to where `inputs` expects it, unless it
+    /// is already there.
+    pub fn fetch(&self, day: u8, inputs: &Inputs) -> Result<Fetched, FetchError> {
+        let path = inputs.path(day, REAL);
+        if path.exists() {
+            return Ok(Fetched::AlreadyCached(path));
+        }
+        let session = self.session.as_deref().ok_or(FetchError::NoSession)?;
+
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| FetchError::Io { path, source }
+        };
+        fs::create_dir_all(inputs.dir()).map_err(io_error(inputs.dir()))?;
+
+        let stamp = inputs.dir().join(LAST_FETCH_FILE);
+        self.wait_for_turn(&stamp);
+        let result = self.backend.get(&self.url(day), session);
+        fs::write(&stamp, millis_since_epoch(SystemTime::now()).to_string())
+            .map_err(io_error(&stamp))?;
+
+        // write beside the input and move it into place, so an interrupted write
+        // never leaves a truncated input that looks cached
+        let partial = path.with_extension("txt.part");
+        fs::write(&partial, result?).map_err(io_error(&partial))?;
+        if let Err(e) = fs::rename(&partial, &path) {
+            let _ = fs::remove_file(&partial);
+            return Err(io_error(&path)(e));
+        }
+        Ok(Fetched::Downloaded(path))
+    }
+
+    fn wait_for_turn(&self, stamp: &Path) {
+        let Some(last) = fs::read_to_string(stamp)
+            .ok()
+            .and_then(|s| s.trim().parse::<u128>().ok())
+        else {
+            return;
+        };
+        // both times are cut to whole milliseconds, which can make the gap look up
+        // to one longer than it was
+        let elapsed = millis_since_epoch(SystemTime::now())
+            .saturating_sub(last)
+            .saturating_sub(1);
+        let remaining = self.min_interval.as_millis().saturating_sub(elapsed);
+        if remaining > 0 {
+            thread::sleep(Duration::from_millis(remaining as u64));
+        }
+    }
+}
+
+fn millis_since_epoch(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::TempInputs;
+    use std::cell::RefCell;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::time::Instant;
+
+    struct MockBackend {
+        requests: RefCell<Vec<(String, String)>>,
+    }
+
+    impl MockBackend {
+        fn new() -> MockBackend {
+            MockBackend {
+                requests: RefCell::new(vec![]),
+            }
+        }
+    }
+
+    impl HttpBackend for &MockBackend {
+        fn get(&self, url: &str, session: &str) -> Result<String, FetchError> {
+            self.requests
+                .borrow_mut()
+                .push((url.to_string(), session.to_string()));
+            Ok(format!("input from {}\n", url))
+        }
+    }
+
+    #[test]
+    fn fetch_should_never_refetch_cached_inputs() {
+        let backend = MockBackend::new();
+        let fetcher = Fetcher::new(&backend, "http://stub/2023/", Some("token".to_string()))
+            .with_min_interval(Duration::ZERO);
+        let mut inputs = TempInputs::new("fetch_cached", &[]);
+
+        let path = inputs.path(2, REAL);
+        assert_eq!(
+            fetcher.fetch(2, &inputs).unwrap(),
+            Fetched::Downloaded(path.clone())
+        );
+        assert_eq!(
+            fetcher.fetch(2, &inputs).unwrap(),
+            Fetched::AlreadyCached(path)
+        );
+        assert_eq!(
+            *backend.requests.borrow(),
+            vec![(
+                "http://stub/2023/day/2/input".to_string(),
+                "token".to_string()
+            )]
+        );
+        assert_eq!(
+            inputs.load(2, REAL).unwrap(),
+            "input from http://stub/2023/day/2/input\n"
+        );
+    }
+
+    #[test]
+    fn fetch_should_replace_partial_downloads() {
+        let backend = MockBackend::new();
+        let fetcher = Fetcher::new(&backend, "http://stub", Some("token".to_string()))
+            .with_min_interval(Duration::ZERO);
+        let partial = "day_4.txt.part";
+        let mut inputs = TempInputs::new("fetch_partial", &[(partial, "Card 1: 4")]);
+
+        let path = inputs.path(4, REAL);
+        assert_eq!(
+            fetcher.fetch(4, &inputs).unwrap(),
+            Fetched::Downloaded(path)
+        );
+        assert!(!inputs.dir().join(partial).exists());
+        assert_eq!(inputs.available(4), vec![REAL]);
+        assert_eq!(
+            inputs.load(4, REAL).unwrap(),
+            "input from http://stub/day/4/input\n"
+        );
+    }
+
+    #[test]
+    fn fetch_should_need_a_session() {
+        let backend = MockBackend::new();
+        let fetcher = Fetcher::new(&backend, "http://stub", None);
+        let inputs = TempInputs::new("fetch_no_session", &[]);
+        assert!(matches!(
+            fetcher.fetch(1, &inputs),
+            Err(FetchError::NoSession)
+        ));
+        assert!(backend.requests.borrow().is_empty());
+    }
+
+    #[test]
+    fn fetch_should_space_out_requests() {
+        let backend = MockBackend::new();
+        let fetcher = Fetcher::new(&backend, "http://stub", Some("token".to_string()))
+            .with_min_interval(Duration::from_millis(200));
+        let inputs = TempInputs::new("fetch_rate_limit", &[]);
+
+        let start = Instant::now();
+        fetcher.fetch(1, &inputs).unwrap();
+        fetcher.fetch(2, &inputs).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn ureq_backend_should_work_against_a_stub_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/2023", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push(line.trim().to_string());
+            }
+            let body = "1abc2\n";
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            request
+        });
+
+        let fetcher = Fetcher::new(UreqBackend::new(), &base_url, Some("token".to_string()))
+            .with_min_interval(Duration::ZERO);
+        let mut inputs = TempInputs::new("fetch_stub_server", &[]);
+        fetcher.fetch(1, &inputs).unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(request[0], "GET /2023/day/1/input HTTP/1.1");
+        assert!(request
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case("cookie: session=token")));
+        assert_eq!(inputs.load(1, REAL).unwrap(), "1abc2\n");
+    }
+}
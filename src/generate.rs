@@ -0,0 +1,224 @@
+//! Random puzzle inputs, for fuzzing and benchmarking.
+//!
+//! Every generator is deterministic for a given seed, and only writes inputs the
+//! day's parser accepts. `size` is the number of lines, and for day 3 also the
+//! width of the schematic.
+
+use crate::days::day_1::ENGLISH_WORDS;
+use crate::days::day_2::{Color, Game, Pull};
+
+/// A small splitmix64 generator, good enough for test data.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`. `n` must not be 0.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// A number in `low..=high`.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// Days that have a generator.
+pub const DAYS: &[u8] = &[1, 2, 3, 4];
+
+/// An input for `day`, or `None` if the day has no generator.
+pub fn input(day: u8, size: usize, seed: u64) -> Option<String> {
+    let mut rng = Rng::new(seed);
+    let lines = match day {
+        1 => day_1(&mut rng, size),
+        2 => day_2(&mut rng, size),
+        3 => day_3(&mut rng, size),
+        4 => day_4(&mut rng, size),
+        _ => return None,
+    };
+    Some(lines.iter().map(|line| format!("{}\n", line)).collect())
+}
+
+/// Calibration lines of letters, digits and spelled-out digits. Each line has at
+/// least one digit, like the real inputs, so both parts have an answer.
+fn day_1(rng: &mut Rng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+            for _ in 0..rng.between(1, 6) {
+                match rng.below(3) {
+                    0 => line.push(char::from(b'1' + rng.below(9) as u8)),
+                    1 => line.push_str(rng.pick(&ENGLISH_WORDS).0),
+                    _ => line.extend(
+                        (0..rng.between(1, 4)).map(|_| char::from(b'a' + rng.below(26) as u8)),
+                    ),
+                }
+            }
+            let at = rng.below(line.len() as u64 + 1) as usize;
+            line.insert(at, char::from(b'1' + rng.below(9) as u8));
+            line
+        })
+        .collect()
+}
+
+/// Games with one to six pulls of up to 20 red, green and blue cubes.
+fn day_2(rng: &mut Rng, size: usize) -> Vec<String> {
+    let colours = [Color::Red, Color::Green, Color::Blue];
+    (0..size)
+        .map(|i| {
+            let pulls = (0..rng.between(1, 6))
+                .map(|_| {
+                    let mut pull = Pull::default();
+                    // at least one colour, so every pull shows some cubes
+                    let first = rng.below(3) as usize;
+                    for (c, colour) in colours.iter().enumerate() {
+                        if c == first || rng.chance(50) {
+                            pull = pull.with(colour.clone(), rng.between(1, 20) as u32);
+                        }
+                    }
+                    pull
+                })
+                .collect();
+            Game::new(i as u32 + 1, pulls).to_string()
+        })
+        .collect()
+}
+
+/// A square schematic with numbers of up to three digits and scattered symbols,
+/// mostly gears. Numbers on a row are always kept apart by at least one cell.
+fn day_3(rng: &mut Rng, size: usize) -> Vec<String> {
+    const SYMBOLS: &[u8] = b"**#+$/@=%&-";
+    (0..size)
+        .map(|_| {
+            let mut row = vec![b'.'; size];
+            let mut x = 0;
+            while x < size {
+                if rng.chance(15) {
+                    let len = (rng.between(1, 3) as usize).min(size - x);
+                    row[x] = b'1' + rng.below(9) as u8;
+                    for cell in &mut row[x + 1..x + len] {
+                        *cell = b'0' + rng.below(10) as u8;
+                    }
+                    // leave the cell after the number empty
+                    x += len + 1;
+                } else {
+                    if rng.chance(8) {
+                        row[x] = *rng.pick(SYMBOLS);
+                    }
+                    x += 1;
+                }
+            }
+            String::from_utf8(row).expect("schematics are ASCII")
+        })
+        .collect()
+}
+
+/// Scratchcards laid out like the real input: ten winning numbers and 25 picks,
+/// all between 1 and 99 and right-aligned. As the puzzle promises, no card wins
+/// copies of cards past the end of the table.
+fn day_4(rng: &mut Rng, size: usize) -> Vec<String> {
+    let id_width = size.to_string().len();
+    (0..size)
+        .map(|i| {
+            let winning = distinct(rng, 10, &[]);
+            // each extra match half as likely as the last, which keeps the
+            // number of copies growing roughly linearly like in the real input
+            let remaining = (size - i - 1).min(winning.len());
+            let mut matches = 0;
+            while matches < remaining && rng.chance(50) {
+                matches += 1;
+            }
+            let mut picks: Vec<u64> = winning[..matches].to_vec();
+            picks.extend(distinct(rng, 25 - matches, &winning));
+            shuffle(rng, &mut picks);
+
+            let format = |numbers: &[u64]| {
+                numbers
+                    .iter()
+                    .map(|n| format!("{:>2}", n))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            };
+            format!(
+                "Card {:>id_width$}: {} | {}",
+                i + 1,
+                format(&winning),
+                format(&picks)
+            )
+        })
+        .collect()
+}
+
+/// `count` different numbers from 1 to 99, none of them in `excluded`.
+fn distinct(rng: &mut Rng, count: usize, excluded: &[u64]) -> Vec<u64> {
+    let mut numbers: Vec<u64> = vec![];
+    while numbers.len() < count {
+        let n = rng.between(1, 99);
+        if !numbers.contains(&n) && !excluded.contains(&n) {
+            numbers.push(n);
+        }
+    }
+    numbers
+}
+
+fn shuffle<T>(rng: &mut Rng, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, rng.below(i as u64 + 1) as usize);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days;
+
+    #[test]
+    fn inputs_should_parse_for_every_day() {
+        for &day in DAYS {
+            for seed in 0..20 {
+                let input = input(day, 200, seed).unwrap();
+                let solution = (days::get(day).unwrap().parse)(&input)
+                    .unwrap_or_else(|e| panic!("day {} seed {}: {}\n{}", day, seed, e, input));
+                solution.part1();
+                solution.part2();
+            }
+        }
+    }
+
+    #[test]
+    fn inputs_should_depend_only_on_the_seed() {
+        assert_eq!(input(3, 10, 7), input(3, 10, 7));
+        assert_ne!(input(3, 10, 7), input(3, 10, 8));
+        assert_eq!(input(2, 25, 1).unwrap().lines().count(), 25);
+        assert_eq!(input(25, 10, 1), None);
+    }
+
+    #[test]
+    fn schematics_should_be_square() {
+        let input = input(3, 30, 5).unwrap();
+        assert!(input.lines().all(|row| row.len() == 30));
+        assert_eq!(input.lines().count(), 30);
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Pos {
+    // y first so that positions sort row by row
+    pub y: usize,
+    pub x: usize,
+}
+
+impl Pos {
+    pub fn new(x: usize, y: usize) -> Pos {
+        Pos { x, y }
+    }
+}
+
+#[derive(Debug, EnumIter, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    LUp,
+    Up,
+    RUp,
+    L,
+    R,
+    LDown,
+    Down,
+    RDown,
+}
+
+impl Direction {
+    pub const ORTHOGONAL: [Direction; 4] =
+        [Direction::Up, Direction::L, Direction::R, Direction::Down];
+
+    /// The (dx, dy) step for this direction, with y growing downwards.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::LUp => (-1, -1),
+            Direction::Up => (0, -1),
+            Direction::RUp => (1, -1),
+            Direction::L => (-1, 0),
+            Direction::R => (1, 0),
+            Direction::LDown => (-1, 1),
+            Direction::Down => (0, 1),
+            Direction::RDown => (1, 1),
+        }
+    }
+}
+
+/// The rows passed to [`Grid::from_rows`] were not all the same length.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaggedRowError {
+    pub row: usize,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for RaggedRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "row {} has {} cells, expected {}",
+            self.row + 1,
+            self.found,
+            self.expected
+        )
+    }
+}
+
+impl std::error::Error for RaggedRowError {}
+
+/// A rectangular grid stored row by row, with (0, 0) in the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, RaggedRowError> {
+        let width = rows.first().map_or(0, |row| row.len());
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (i, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(RaggedRowError {
+                    row: i,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+            cells.extend(row);
+        }
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.x < self.width && pos.y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.cells[pos.y * self.width + pos.x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.cells[pos.y * self.width + pos.x])
+        } else {
+            None
+        }
+    }
+
+    /// The position one step from `pos` in `dir`, if it is still inside the grid.
+    pub fn step(&self, pos: Pos, dir: Direction) -> Option<Pos> {
+        let (dx, dy) = dir.offset();
+        let next = Pos {
+            x: pos.x.checked_add_signed(dx)?,
+            y: pos.y.checked_add_signed(dy)?,
+        };
+        self.contains(next).then_some(next)
+    }
+
+    /// Up, left, right and down neighbours that are inside the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.neighbours_in(pos, Direction::ORTHOGONAL.into_iter())
+    }
+
+    /// All eight neighbours, including diagonals, that are inside the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.neighbours_in(pos, Direction::iter())
+    }
+
+    fn neighbours_in<'a>(
+        &'a self,
+        pos: Pos,
+        directions: impl Iterator<Item = Direction> + 'a,
+    ) -> impl Iterator<Item = (Pos, &'a T)> + 'a {
+        directions.filter_map(move |dir| {
+            let next = self.step(pos, dir)?;
+            Some((next, &self[next]))
+        })
+    }
+
+    /// Walks from `start` (inclusive) in `dir` until it leaves the grid.
+    pub fn ray(&self, start: Pos, dir: Direction) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        let first = self.contains(start).then_some(start);
+        std::iter::successors(first, move |&pos| self.step(pos, dir)).map(|pos| (pos, &self[pos]))
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.ray(Pos { x: 0, y }, Direction::R)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.ray(Pos { x, y: 0 }, Direction::Down)
+    }
+
+    /// Walks down and to the right from `start`.
+    pub fn diagonal(&self, start: Pos) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.ray(start, Direction::RDown)
+    }
+
+    /// Walks down and to the left from `start`.
+    pub fn anti_diagonal(&self, start: Pos) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.ray(start, Direction::LDown)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        // chunks panics on a zero size, and a zero-width grid has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.cells.iter().enumerate().map(move |(i, cell)| {
+            let pos = Pos {
+                x: i % self.width,
+                y: i / self.width,
+            };
+            (pos, cell)
+        })
+    }
+}
+
+impl Grid<char> {
+    pub fn from_text(input: &str) -> Result<Grid<char>, RaggedRowError> {
+        Grid::from_rows(input.lines().map(|line| line.chars().collect()).collect())
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", pos, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", pos, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gen_grid() -> Grid<char> {
+        Grid::from_text("abc\ndef\nghi").unwrap()
+    }
+
+    #[test]
+    fn get_should_be_bounds_checked() {
+        let grid = gen_grid();
+        assert_eq!(grid.get(Pos::new(2, 1)), Some(&'f'));
+        assert_eq!(grid.get(Pos::new(3, 1)), None);
+        assert_eq!(grid.get(Pos::new(0, 3)), None);
+    }
+
+    #[test]
+    fn neighbours_should_stay_inside_grid() {
+        let grid = gen_grid();
+        let corner: Vec<char> = grid.neighbours8(Pos::new(0, 0)).map(|(_, c)| *c).collect();
+        assert_eq!(corner, vec!['b', 'd', 'e']);
+        let middle: Vec<char> = grid.neighbours4(Pos::new(1, 1)).map(|(_, c)| *c).collect();
+        assert_eq!(middle, vec!['b', 'd', 'f', 'h']);
+        assert_eq!(grid.neighbours8(Pos::new(1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn lines_should_walk_rows_columns_and_diagonals() {
+        let grid = gen_grid();
+        let collect =
+            |it: &mut dyn Iterator<Item = (Pos, &char)>| it.map(|(_, c)| *c).collect::<String>();
+        assert_eq!(collect(&mut grid.row(1)), "def");
+        assert_eq!(collect(&mut grid.column(2)), "cfi");
+        assert_eq!(collect(&mut grid.diagonal(Pos::new(0, 0))), "aei");
+        assert_eq!(collect(&mut grid.anti_diagonal(Pos::new(2, 0))), "ceg");
+        assert_eq!(collect(&mut grid.row(3)), "");
+    }
+
+    #[test]
+    fn from_rows_should_reject_ragged_rows() {
+        let err = Grid::from_text("abc\nde\nfgh").unwrap_err();
+        assert_eq!(
+            err,
+            RaggedRowError {
+                row: 1,
+                expected: 3,
+                found: 2
+            }
+        );
+    }
+
+    #[test]
+    fn display_should_round_trip_text() {
+        assert_eq!(gen_grid().to_string(), "abc\ndef\nghi");
+    }
+}
@@ -0,0 +1,218 @@
+//! Finding and loading puzzle inputs.
+//!
+//! Each day can have several named inputs in the inputs directory. The real puzzle
+//! input lives in `day_3.txt`, and any other input called `name` lives in
+//! `day_3.name.txt`, for example `day_3.example1.txt`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name of the real puzzle input.
+pub const REAL: &str = "real";
+
+#[derive(Debug)]
+pub enum InputError {
+    Missing {
+        day: u8,
+        name: String,
+        path: PathBuf,
+        available: Vec<String>,
+    },
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Missing {
+                day,
+                name,
+                path,
+                available,
+            } => {
+                write!(
+                    f,
+                    "No {} input for day {}, expected it at {}",
+                    name,
+                    day,
+                    path.display()
+                )?;
+                if !available.is_empty() {
+                    write!(f, " (found: {})", available.join(", "))?;
+                }
+                Ok(())
+            }
+            InputError::Io { path, source } => {
+                write!(f, "Could not read {}: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Puzzle inputs in one directory. Each input is read from disk at most once.
+#[derive(Debug)]
+pub struct Inputs {
+    dir: PathBuf,
+    cache: HashMap<(u8, String), String>,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Inputs {
+        Inputs {
+            dir: dir.into(),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Where the input called `name` for `day` is expected to be.
+    pub fn path(&self, day: u8, name: &str) -> PathBuf {
+        match name {
+            REAL => self.dir.join(format!("day_{}.txt", day)),
+            name => self.dir.join(format!("day_{}.{}.txt", day, name)),
+        }
+    }
+
+    /// The names of every input present for `day`, sorted.
+    pub fn available(&self, day: u8) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        let prefix = format!("day_{}", day);
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter_map(|file| {
+                let rest = file.strip_prefix(&prefix)?.strip_suffix(".txt")?;
+                match rest {
+                    "" => Some(REAL.to_string()),
+                    rest => rest.strip_prefix('.').map(|name| name.to_string()),
+                }
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn load(&mut self, day: u8, name: &str) -> Result<&str, InputError> {
+        let key = (day, name.to_string());
+        if !self.cache.contains_key(&key) {
+            let path = self.path(day, name);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    return Err(InputError::Missing {
+                        day,
+                        name: name.to_string(),
+                        path,
+                        available: self.available(day),
+                    })
+                }
+                Err(source) => return Err(InputError::Io { path, source }),
+            };
+            self.cache.insert(key.clone(), content);
+        }
+        Ok(&self.cache[&key])
+    }
+}
+
+/// An inputs directory for a test, removed again when the test is done.
+#[cfg(test)]
+pub(crate) struct TempInputs {
+    dir: PathBuf,
+    inputs: Inputs,
+}
+
+#[cfg(test)]
+impl TempInputs {
+    /// A fresh directory for the test called `test`, holding `files`.
+    pub(crate) fn new(test: &str, files: &[(&str, &str)]) -> TempInputs {
+        let dir =
+            std::env::temp_dir().join(format!("advent_of_code_{}_{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        TempInputs {
+            inputs: Inputs::new(&dir),
+            dir,
+        }
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempInputs {
+    type Target = Inputs;
+
+    fn deref(&self) -> &Inputs {
+        &self.inputs
+    }
+}
+
+#[cfg(test)]
+impl std::ops::DerefMut for TempInputs {
+    fn deref_mut(&mut self) -> &mut Inputs {
+        &mut self.inputs
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempInputs {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_should_find_named_inputs() {
+        let mut inputs = TempInputs::new(
+            "named",
+            &[
+                ("day_3.txt", "real"),
+                ("day_3.example1.txt", "ex1"),
+                ("day_13.txt", "other day"),
+            ],
+        );
+        assert_eq!(inputs.load(3, REAL).unwrap(), "real");
+        assert_eq!(inputs.load(3, "example1").unwrap(), "ex1");
+        assert_eq!(inputs.available(3), vec!["example1", "real"]);
+        assert_eq!(inputs.available(1), Vec::<String>::new());
+    }
+
+    #[test]
+    fn load_should_cache_contents() {
+        let mut inputs = TempInputs::new("cache", &[("day_1.txt", "first")]);
+        assert_eq!(inputs.load(1, REAL).unwrap(), "first");
+        fs::write(inputs.path(1, REAL), "second").unwrap();
+        assert_eq!(inputs.load(1, REAL).unwrap(), "first");
+    }
+
+    #[test]
+    fn missing_input_should_name_expected_path() {
+        let mut inputs = TempInputs::new("missing", &[("day_2.example1.txt", "")]);
+        let err = inputs.load(2, "example2").unwrap_err();
+        let expected = inputs.path(2, "example2");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "No example2 input for day 2, expected it at {} (found: example1)",
+                expected.display()
+            )
+        );
+    }
+}
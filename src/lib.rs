@@ -1,2 +1,13 @@
-pub mod utilities;
+pub mod answers;
+pub mod bench;
+pub mod config;
 pub mod days;
+pub mod error;
+pub mod fetch;
+pub mod generate;
+pub mod grid;
+pub mod inputs;
+pub mod reference;
+pub mod scanner;
+pub mod solution;
+pub mod utilities;
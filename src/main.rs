@@ -1,48 +1,484 @@
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, BufReader, ErrorKind, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use advent_of_code::answers::{Answers, Verdict};
+use advent_of_code::bench;
+use advent_of_code::config::Config;
 use advent_of_code::days;
-use advent_of_code::days::day_2::Pull;
-use advent_of_code::utilities;
-
-fn main() {
-    // day_1();
-    // day_2();
-    // day_3();
-    day_4();
-}
-
-fn day_1() {
-    let file_path = "src/input_files/day_1.txt";
-    let lines = utilities::read_file_by_line(file_path)
-        .unwrap()
-        .iter()
-        .map(|s| String::from(s))
-        .collect();
-    let result = days::day_1::sum_all_lines(lines);
-    println!("Result for day 1 is {}", result);
-}
-
-fn day_2() {
-    let lines = utilities::read_file_by_line("src/input_files/day_2.txt").unwrap();
-    let max_pull = Pull::new(12, 13, 14);
-    let mut result = days::day_2::sum_valid_ids_from_lines(&lines, &max_pull);
-    println!("Result for day 2 part 1 is {}", result);
-    result = days::day_2::power_from_lines(&lines);
-    println!("Result for day 2 part 2 is {}", result);
-}
-
-fn day_3() {
-    let content = fs::read_to_string("src/input_files/day_3.txt").unwrap();
-    let engine = days::day_3::Engine::from_string(content);
-    let part1 = engine.sum_valid_nums();
-    println!("Result for day 3 part 1 is {}", part1);
-    println!("Result for day 3 part 2 is {}", engine.sum_gear_ratios());
-}
-
-fn day_4() {
-    let lines = utilities::read_file_by_line("src/input_files/day_4.txt").unwrap();
-    let result = days::day_4::sum_all_cards(&lines);
-    println!("Result for day 4 part 1 is {}", result);
-    let result = days::day_4::sum_all_cards_with_new_rules(&lines);
-    println!("Result for day 4 part 2 is {}", result);
+use advent_of_code::days::day_1::{self, Strategy};
+use advent_of_code::days::day_2::{self, Day2};
+use advent_of_code::days::day_3::{Engine, Rules};
+use advent_of_code::fetch::{Fetched, Fetcher, UreqBackend};
+use advent_of_code::generate;
+use advent_of_code::inputs::{self, Inputs};
+use advent_of_code::reference;
+use advent_of_code::solution::{Day, Solution};
+
+#[derive(Parser)]
+#[command(version, about = "Advent of Code 2023 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run one day's solution, or every day with --all
+    Run(RunArgs),
+    /// Time the parse phase and each part of every day
+    Bench(BenchArgs),
+    /// Download a day's input into the inputs directory
+    Fetch(FetchArgs),
+    /// Show how a day 1 or day 2 input leads to its answers
+    Explain(ExplainArgs),
+    /// Print a random input for a day
+    Gen(GenArgs),
+    /// Check solutions against the simple reference ones on random inputs
+    Diff(DiffArgs),
+    /// Draw a day 3 schematic with its parts and gears highlighted
+    Viz(VizArgs),
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Day to run
+    #[arg(short, long, required_unless_present = "all", value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: Option<u8>,
+
+    /// Only run this part (runs both parts when omitted)
+    #[arg(short, long, conflicts_with = "all", value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Input file to use instead of one from the inputs directory, or - for stdin
+    #[arg(short, long, conflicts_with = "all")]
+    input: Option<PathBuf>,
+
+    /// Same as --input
+    #[arg(value_name = "FILE", conflicts_with_all = ["input", "all"])]
+    file: Option<PathBuf>,
+
+    /// Which of the day's inputs to use, e.g. example1
+    #[arg(short, long, default_value = inputs::REAL, conflicts_with_all = ["input", "file"])]
+    name: String,
+
+    /// Run every implemented day
+    #[arg(long, conflicts_with = "day")]
+    all: bool,
+
+    /// Recorded answers to check results against. Defaults to answers.toml in the
+    /// inputs directory, which is only used for the real inputs
+    #[arg(short, long)]
+    answers: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// Only benchmark this day
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: Option<u8>,
+
+    /// How many times to run each day
+    #[arg(short, long, default_value_t = 10)]
+    runs: usize,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = BenchFormat::Table)]
+    format: BenchFormat,
+}
+
+#[derive(Args)]
+struct FetchArgs {
+    /// Day to download
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+}
+
+#[derive(Args)]
+struct ExplainArgs {
+    /// Day to explain
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2))]
+    day: u8,
+
+    /// Day 1 only: part whose rules to apply, 1 counts digits only and 2 spelled-out
+    /// digits too
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Input file to use instead of one from the inputs directory, or - for stdin
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Which of the day's inputs to use, e.g. example1
+    #[arg(short, long, default_value = inputs::REAL, conflicts_with = "input")]
+    name: String,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ExplainFormat::Table)]
+    format: ExplainFormat,
+}
+
+#[derive(Args)]
+struct GenArgs {
+    /// Day to generate an input for
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Number of lines, which is also the width of a day 3 schematic
+    #[arg(short, long, default_value_t = 100)]
+    size: usize,
+
+    /// Seed for the generator; the same seed always gives the same input
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Only check this day
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: Option<u8>,
+
+    /// Size of each generated input, as for gen
+    #[arg(short, long, default_value_t = 50)]
+    size: usize,
+
+    /// How many inputs to try per day
+    #[arg(short, long, default_value_t = 100)]
+    runs: u64,
+
+    /// Seed of the first input; later inputs use the following seeds
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+#[derive(Args)]
+struct VizArgs {
+    /// Day to draw; only day 3 can be drawn so far
+    #[arg(short, long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Input file to use instead of one from the inputs directory, or - for stdin
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+
+    /// Which of the day's inputs to use, e.g. example1
+    #[arg(short, long, default_value = inputs::REAL, conflicts_with = "input")]
+    name: String,
+
+    /// Leave out colours, which are also left out when not writing to a terminal
+    #[arg(long)]
+    plain: bool,
+
+    /// Draw the schematic as text, or export which symbols touch which numbers as a graph
+    #[arg(short, long, value_enum, default_value_t = VizFormat::Text)]
+    format: VizFormat,
+
+    /// Pad short rows with empty cells instead of rejecting them
+    #[arg(long)]
+    pad: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExplainFormat {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum VizFormat {
+    Text,
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BenchFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = Config::load()
+        .map_err(|e| e.to_string())
+        .and_then(|config| {
+            let mut inputs = Inputs::new(&config.inputs_dir);
+            match cli.command {
+                Command::Run(args) => run(args, &mut inputs),
+                Command::Bench(args) => bench(args, &mut inputs),
+                Command::Fetch(args) => fetch(args, &config, &inputs),
+                Command::Explain(args) => explain(args, &mut inputs),
+                Command::Gen(args) => gen(args),
+                Command::Diff(args) => diff(args),
+                Command::Viz(args) => viz(args, &mut inputs),
+            }
+        });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: RunArgs, inputs: &mut Inputs) -> Result<(), String> {
+    args.input = args.input.or(args.file.take());
+    let answers_path = match (&args.answers, &args.input) {
+        (Some(path), _) => Some(path.clone()),
+        (None, None) if args.name == inputs::REAL => Some(inputs.dir().join(ANSWERS_FILE)),
+        // recorded answers are for the real inputs, not examples or --input files
+        (None, _) => None,
+    };
+    let answers = match answers_path {
+        Some(path) => load_answers(&path, args.answers.is_some())?,
+        None => Answers::default(),
+    };
+
+    let mut all_passed = true;
+    if args.all {
+        for day in days::DAYS {
+            let input = inputs
+                .load(day.day, inputs::REAL)
+                .map_err(|e| e.to_string())?;
+            let solution = parse_day(day, input)?;
+            all_passed &= run_parts(day.day, solution.as_ref(), None, &answers);
+        }
+    } else {
+        // clap guarantees a day whenever --all is absent
+        let day = args.day.unwrap();
+        let day = days::get(day).ok_or_else(|| format!("Day {} is not implemented yet", day))?;
+        let solution = match &args.input {
+            Some(path) if path.as_os_str() == "-" => (day.read)(&mut io::stdin().lock())
+                .map_err(|e| format!("Could not parse stdin: {}", e))?,
+            Some(path) => {
+                let file = File::open(path)
+                    .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+                (day.read)(&mut BufReader::new(file))
+                    .map_err(|e| format!("Could not parse {}: {}", path.display(), e))?
+            }
+            None => {
+                let input = inputs
+                    .load(day.day, &args.name)
+                    .map_err(|e| e.to_string())?;
+                parse_day(day, input)?
+            }
+        };
+        all_passed = run_parts(day.day, solution.as_ref(), args.part, &answers);
+    }
+
+    match all_passed {
+        true => Ok(()),
+        false => Err("Some answers did not match the recorded ones".to_string()),
+    }
+}
+
+const ANSWERS_FILE: &str = "answers.toml";
+
+/// A missing answers file is only an error when it was asked for explicitly.
+fn load_answers(path: &Path, required: bool) -> Result<Answers, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound && !required => return Ok(Answers::default()),
+        Err(e) => {
+            return Err(format!(
+                "Could not read answers from {}: {}",
+                path.display(),
+                e
+            ))
+        }
+    };
+    Answers::from_toml(&text).map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+}
+
+fn parse_day(day: &Day, input: &str) -> Result<Box<dyn Solution>, String> {
+    (day.parse)(input).map_err(|e| format!("Could not parse the input for day {}: {}", day.day, e))
+}
+
+/// Runs the requested parts, returning whether none of them contradicted a
+/// recorded answer.
+fn run_parts(day: u8, solution: &dyn Solution, part: Option<u8>, answers: &Answers) -> bool {
+    let mut passed = true;
+    for n in [1, 2] {
+        if !wants(part, n) {
+            continue;
+        }
+        let result = match n {
+            1 => solution.part1(),
+            _ => solution.part2(),
+        }
+        .to_string();
+        let verdict = answers.check(day, n, &result);
+        println!(
+            "Result for day {} part {} is {} ({})",
+            day, n, result, verdict
+        );
+        passed &= !matches!(verdict, Verdict::Fail { .. });
+    }
+    passed
+}
+
+fn bench(args: BenchArgs, inputs: &mut Inputs) -> Result<(), String> {
+    let selected: Vec<&Day> = match args.day {
+        Some(day) => {
+            vec![days::get(day).ok_or_else(|| format!("Day {} is not implemented yet", day))?]
+        }
+        None => days::DAYS.iter().collect(),
+    };
+
+    let mut timings = vec![];
+    for day in selected {
+        let input = match inputs.load(day.day, inputs::REAL) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Skipping day {}: {}", day.day, e);
+                continue;
+            }
+        };
+        let day_timings = bench::bench_day(day, input, args.runs)
+            .map_err(|e| format!("Could not parse the input for day {}: {}", day.day, e))?;
+        timings.push(day_timings);
+    }
+
+    let output = match args.format {
+        BenchFormat::Table => bench::to_table(&timings),
+        BenchFormat::Json => bench::to_json(&timings),
+        BenchFormat::Csv => bench::to_csv(&timings),
+    };
+    print!("{}", output);
+    Ok(())
+}
+
+fn fetch(args: FetchArgs, config: &Config, inputs: &Inputs) -> Result<(), String> {
+    let fetcher = Fetcher::new(UreqBackend::new(), &config.base_url, config.session.clone());
+    match fetcher.fetch(args.day, inputs).map_err(|e| e.to_string())? {
+        Fetched::Downloaded(path) => println!("Saved day {} input to {}", args.day, path.display()),
+        Fetched::AlreadyCached(path) => {
+            println!("Day {} input is already at {}", args.day, path.display())
+        }
+    }
+    Ok(())
+}
+
+/// The whole text of `input` (- for stdin), or else of the day's named input.
+fn read_text(
+    input: &Option<PathBuf>,
+    day: u8,
+    name: &str,
+    inputs: &mut Inputs,
+) -> Result<String, String> {
+    match input {
+        Some(path) if path.as_os_str() == "-" => {
+            io::read_to_string(io::stdin()).map_err(|e| format!("Could not read stdin: {}", e))
+        }
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e)),
+        None => Ok(inputs
+            .load(day, name)
+            .map_err(|e| e.to_string())?
+            .to_string()),
+    }
+}
+
+fn explain(args: ExplainArgs, inputs: &mut Inputs) -> Result<(), String> {
+    let input = read_text(&args.input, args.day, &args.name, inputs)?;
+
+    let output = match args.day {
+        1 => {
+            let strategy = match args.part {
+                Some(1) => Strategy::DigitsOnly,
+                _ => Strategy::SpelledOut,
+            };
+            let explanations = day_1::explain(input.lines(), strategy);
+            match args.format {
+                ExplainFormat::Table => day_1::explain_table(&explanations),
+                ExplainFormat::Json => day_1::explain_json(&explanations),
+            }
+        }
+        _ => {
+            if args.part.is_some() {
+                return Err("--part only applies to day 1".to_string());
+            }
+            let games = Day2::parse(&input)
+                .map_err(|e| format!("Could not parse the input for day 2: {}", e))?;
+            let bag = day_2::puzzle_bag();
+            match args.format {
+                ExplainFormat::Table => games.analysis_table(&bag),
+                ExplainFormat::Json => games.analysis_json(&bag),
+            }
+        }
+    };
+    print!("{}", output);
+    Ok(())
+}
+
+fn gen(args: GenArgs) -> Result<(), String> {
+    let input = generate::input(args.day, args.size, args.seed)
+        .ok_or_else(|| format!("There is no input generator for day {}", args.day))?;
+    print!("{}", input);
+    Ok(())
+}
+
+fn diff(args: DiffArgs) -> Result<(), String> {
+    let selected: Vec<u8> = match args.day {
+        // differential says what the day is missing, if anything
+        Some(day) => vec![day],
+        None => reference::DAYS
+            .iter()
+            .copied()
+            .filter(|day| generate::DAYS.contains(day))
+            .collect(),
+    };
+    let end = args.seed.checked_add(args.runs).ok_or_else(|| {
+        format!(
+            "Cannot try {} inputs from seed {}, the seeds would run past {}",
+            args.runs,
+            args.seed,
+            u64::MAX
+        )
+    })?;
+    for day in selected {
+        reference::differential(day, args.size, args.seed..end).map_err(|e| e.to_string())?;
+        println!(
+            "Day {} agreed with its reference on {} inputs",
+            day, args.runs
+        );
+    }
+    Ok(())
+}
+
+fn viz(args: VizArgs, inputs: &mut Inputs) -> Result<(), String> {
+    if args.day != 3 {
+        return Err(format!("Day {} cannot be drawn, only day 3 can", args.day));
+    }
+    let input = read_text(&args.input, args.day, &args.name, inputs)?;
+    let engine = if args.pad {
+        let (engine, padded) = Engine::from_ragged_lines(input.lines(), Rules::default())
+            .map_err(|e| format!("Could not parse the input for day 3: {}", e))?;
+        if !padded.is_empty() {
+            let rows: Vec<String> = padded.iter().map(|row| row.to_string()).collect();
+            eprintln!("Padded rows {}", rows.join(", "));
+        }
+        engine
+    } else {
+        Engine::from_string(input)
+            .map_err(|e| format!("Could not parse the input for day 3: {}", e))?
+    };
+    let out = match args.format {
+        VizFormat::Text => engine.render(!args.plain && io::stdout().is_terminal()),
+        VizFormat::Dot => engine.graph_dot(),
+        VizFormat::Json => engine.graph_json(),
+    };
+    print!("{}", out);
+    Ok(())
+}
+
+fn wants(part: Option<u8>, n: u8) -> bool {
+    part.is_none_or(|p| p == n)
 }
@@ -0,0 +1,306 @@
+//! Slow, simple solutions to check the real ones against.
+//!
+//! These read the input directly rather than through the days' parsers, and
+//! favour the most literal reading of each puzzle over speed. [`differential`]
+//! runs them next to the registered solutions on generated inputs.
+
+use std::fmt;
+
+use crate::days;
+use crate::generate;
+
+/// Days that have a reference solution.
+pub const DAYS: &[u8] = &[1, 2, 3, 4];
+
+/// Both answers for `day`, or `None` if it has no reference solution. The input
+/// is assumed to be well formed.
+pub fn solve(day: u8, input: &str) -> Option<[String; 2]> {
+    let answers = match day {
+        1 => day_1(input),
+        2 => day_2(input),
+        3 => day_3(input),
+        4 => day_4(input),
+        _ => return None,
+    };
+    Some(answers.map(|a| a.to_string()))
+}
+
+fn day_1(input: &str) -> [u64; 2] {
+    const WORDS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+    let calibration = |line: &str, words: bool| {
+        // try every position, so overlapping words are all found
+        let digits: Vec<u64> = (0..line.len())
+            .filter_map(|i| {
+                let rest = &line[i..];
+                let c = rest.chars().next().unwrap();
+                if let Some(d) = c.to_digit(10) {
+                    return Some(d as u64);
+                }
+                if !words {
+                    return None;
+                }
+                (0..WORDS.len())
+                    .find(|&w| rest.starts_with(WORDS[w]))
+                    .map(|w| w as u64 + 1)
+            })
+            .collect();
+        match (digits.first(), digits.last()) {
+            (Some(first), Some(last)) => first * 10 + last,
+            _ => 0,
+        }
+    };
+    [false, true].map(|words| input.lines().map(|line| calibration(line, words)).sum())
+}
+
+fn day_2(input: &str) -> [u64; 2] {
+    let mut possible = 0;
+    let mut power = 0;
+    for line in input.lines() {
+        let (game, pulls) = line.split_once(": ").unwrap();
+        let id: u64 = game["Game ".len()..].parse().unwrap();
+        let (mut red, mut green, mut blue) = (0, 0, 0);
+        let mut ok = true;
+        for cubes in pulls.split([';', ',']) {
+            let (count, colour) = cubes.trim().split_once(' ').unwrap();
+            let count: u64 = count.parse().unwrap();
+            let (seen, limit) = match colour {
+                "red" => (&mut red, 12),
+                "green" => (&mut green, 13),
+                "blue" => (&mut blue, 14),
+                _ => panic!("unexpected colour {}", colour),
+            };
+            *seen = (*seen).max(count);
+            ok &= count <= limit;
+        }
+        if ok {
+            possible += id;
+        }
+        power += red * green * blue;
+    }
+    [possible, power]
+}
+
+/// Finds every number, then looks at every cell of the box around it.
+fn day_3(input: &str) -> [u64; 2] {
+    let mut grid: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+    for row in grid.iter_mut() {
+        row.resize(width, '.');
+    }
+
+    // (value, row, first column, last column)
+    let mut numbers = vec![];
+    for (y, row) in grid.iter().enumerate() {
+        let mut x = 0;
+        while x < width {
+            if row[x].is_ascii_digit() {
+                let start = x;
+                while x < width && row[x].is_ascii_digit() {
+                    x += 1;
+                }
+                let value: u64 = row[start..x].iter().collect::<String>().parse().unwrap();
+                numbers.push((value, y, start, x - 1));
+            } else {
+                x += 1;
+            }
+        }
+    }
+    let touches = |&(_, y, first, last): &(u64, usize, usize, usize), cy: usize, cx: usize| {
+        cy + 1 >= y && cy <= y + 1 && cx + 1 >= first && cx <= last + 1
+    };
+
+    let mut parts = 0;
+    for number in &numbers {
+        let symbol_nearby = (0..grid.len()).any(|cy| {
+            (0..width).any(|cx| {
+                let c = grid[cy][cx];
+                touches(number, cy, cx) && c != '.' && !c.is_ascii_digit()
+            })
+        });
+        if symbol_nearby {
+            parts += number.0;
+        }
+    }
+
+    let mut ratios = 0;
+    for (cy, row) in grid.iter().enumerate() {
+        for (cx, &c) in row.iter().enumerate() {
+            if c != '*' {
+                continue;
+            }
+            let nearby: Vec<u64> = numbers
+                .iter()
+                .filter(|n| touches(n, cy, cx))
+                .map(|n| n.0)
+                .collect();
+            if nearby.len() == 2 {
+                ratios += nearby[0] * nearby[1];
+            }
+        }
+    }
+    [parts, ratios]
+}
+
+fn day_4(input: &str) -> [u64; 2] {
+    let matches: Vec<usize> = input
+        .lines()
+        .map(|line| {
+            let (_, numbers) = line.split_once(':').unwrap();
+            let (winning, picks) = numbers.split_once('|').unwrap();
+            let winning: Vec<&str> = winning.split_whitespace().collect();
+            picks
+                .split_whitespace()
+                .filter(|n| winning.contains(n))
+                .count()
+        })
+        .collect();
+
+    let points = matches
+        .iter()
+        .map(|&m| if m == 0 { 0 } else { 1 << (m - 1) })
+        .sum();
+    let mut copies = vec![1; matches.len()];
+    for (i, &m) in matches.iter().enumerate() {
+        for j in i + 1..=i + m {
+            copies[j] += copies[i];
+        }
+    }
+    [points, copies.iter().sum()]
+}
+
+/// The first input on which a solution and its reference disagreed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub day: u8,
+    pub seed: u64,
+    pub part: u8,
+    pub expected: String,
+    pub actual: String,
+    pub input: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {} part {} gave {} but the reference gave {}, on the input from seed {}:\n{}",
+            self.day, self.part, self.actual, self.expected, self.seed, self.input
+        )
+    }
+}
+
+/// Why [`differential`] found a day wanting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffError {
+    /// The day cannot be compared, because it lacks `missing`.
+    Unsupported {
+        day: u8,
+        missing: &'static str,
+    },
+    Mismatch(Mismatch),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::Unsupported { day, missing } => write!(f, "Day {} has no {}", day, missing),
+            DiffError::Mismatch(mismatch) => mismatch.fmt(f),
+        }
+    }
+}
+
+/// Runs `day`'s solution and reference solution on inputs generated from each of
+/// `seeds`, stopping at the first disagreement. Days without a solution, a
+/// generator or a reference are an error rather than a pass.
+pub fn differential(
+    day: u8,
+    size: usize,
+    seeds: impl IntoIterator<Item = u64>,
+) -> Result<(), DiffError> {
+    let unsupported = |missing| DiffError::Unsupported { day, missing };
+    let Some(registered) = days::get(day) else {
+        return Err(unsupported("solution"));
+    };
+    if !generate::DAYS.contains(&day) {
+        return Err(unsupported("generator"));
+    }
+    if !DAYS.contains(&day) {
+        return Err(unsupported("reference solution"));
+    }
+    for seed in seeds {
+        let input = generate::input(day, size, seed).ok_or(unsupported("generator"))?;
+        let expected = solve(day, &input).ok_or(unsupported("reference solution"))?;
+        let mismatch = |part: u8, actual: String| {
+            DiffError::Mismatch(Mismatch {
+                day,
+                seed,
+                part,
+                expected: expected[part as usize - 1].clone(),
+                actual,
+                input: input.clone(),
+            })
+        };
+        let solution = match (registered.parse)(&input) {
+            Ok(solution) => solution,
+            Err(e) => return Err(mismatch(1, format!("a parse error ({})", e))),
+        };
+        let actual = [solution.part1().to_string(), solution.part2().to_string()];
+        for part in [1, 2] {
+            if actual[part as usize - 1] != expected[part as usize - 1] {
+                return Err(mismatch(part, actual[part as usize - 1].clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_should_solve_the_examples() {
+        for &day in DAYS {
+            for example in days::get(day).unwrap().examples {
+                let answers = solve(day, example.input).unwrap();
+                for part in [1, 2] {
+                    if let Some(expected) = example.expected(part) {
+                        assert_eq!(
+                            answers[part as usize - 1],
+                            expected,
+                            "day {} {}",
+                            day,
+                            example.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solutions_should_agree_with_references() {
+        for &day in DAYS {
+            if let Err(mismatch) = differential(day, 30, 0..25) {
+                panic!("{}", mismatch);
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_days_should_not_pass() {
+        assert_eq!(
+            differential(25, 10, 0..1),
+            Err(DiffError::Unsupported {
+                day: 25,
+                missing: "solution"
+            })
+        );
+        assert_eq!(
+            differential(25, 10, 0..1).unwrap_err().to_string(),
+            "Day 25 has no solution"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Multi-pattern matching with an Aho-Corasick automaton.
+//!
+//! The automaton works on bytes and can be fed them in any order, so matching a
+//! reversed text against reversed patterns scans a string from its end without
+//! copying it.
+
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// Index of the pattern in the list the automaton was built from.
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl PatternMatch {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Automaton {
+    children: Vec<Vec<(u8, usize)>>,
+    fail: Vec<usize>,
+    // every pattern that ends at a state, including those reached through fail links
+    outputs: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+    max_len: usize,
+}
+
+const ROOT: usize = 0;
+
+impl Automaton {
+    /// Builds an automaton over `patterns`. Empty patterns never match.
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Automaton {
+        let mut automaton = Automaton {
+            children: vec![vec![]],
+            fail: vec![ROOT],
+            outputs: vec![vec![]],
+            lengths: patterns.iter().map(|p| p.as_ref().len()).collect(),
+            max_len: patterns.iter().map(|p| p.as_ref().len()).max().unwrap_or(0),
+        };
+
+        for (index, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut state = ROOT;
+            for &b in pattern {
+                state = match automaton.goto(state, b) {
+                    Some(next) => next,
+                    None => automaton.add_state(state, b),
+                };
+            }
+            automaton.outputs[state].push(index);
+        }
+
+        automaton.link_failures();
+        automaton
+    }
+
+    fn add_state(&mut self, parent: usize, b: u8) -> usize {
+        let state = self.children.len();
+        self.children.push(vec![]);
+        self.fail.push(ROOT);
+        self.outputs.push(vec![]);
+        self.children[parent].push((b, state));
+        state
+    }
+
+    /// Breadth first, so every state's fail link is known before its children need it.
+    fn link_failures(&mut self) {
+        let mut queue: Vec<usize> = self.children[ROOT].iter().map(|&(_, s)| s).collect();
+        let mut i = 0;
+        while i < queue.len() {
+            let state = queue[i];
+            i += 1;
+            for (b, child) in self.children[state].clone() {
+                let fail = self.step(self.fail[state], b);
+                self.fail[child] = fail;
+                let inherited = self.outputs[fail].clone();
+                self.outputs[child].extend(inherited);
+                queue.push(child);
+            }
+        }
+    }
+
+    fn goto(&self, state: usize, b: u8) -> Option<usize> {
+        self.children[state]
+            .iter()
+            .find(|&&(c, _)| c == b)
+            .map(|&(_, next)| next)
+    }
+
+    fn step(&self, mut state: usize, b: u8) -> usize {
+        loop {
+            if let Some(next) = self.goto(state, b) {
+                return next;
+            }
+            if state == ROOT {
+                return ROOT;
+            }
+            state = self.fail[state];
+        }
+    }
+
+    /// The match that starts earliest in the order the bytes are fed, preferring the
+    /// longest when several start at the same place. Stops reading as soon as no
+    /// better match is possible.
+    pub fn find_first(&self, bytes: impl IntoIterator<Item = u8>) -> Option<PatternMatch> {
+        let mut state = ROOT;
+        let mut best: Option<PatternMatch> = None;
+        for (i, b) in bytes.into_iter().enumerate() {
+            if best.is_some_and(|best| i >= best.start + self.max_len) {
+                break;
+            }
+            state = self.step(state, b);
+            for m in self.matches_ending_at(state, i + 1) {
+                if best.is_none_or(|best| {
+                    (m.start, Reverse(m.len())) < (best.start, Reverse(best.len()))
+                }) {
+                    best = Some(m);
+                }
+            }
+        }
+        best
+    }
+
+    /// Every match, including overlapping ones, ordered by start and then end.
+    pub fn find_all(&self, haystack: &[u8]) -> Vec<PatternMatch> {
+        let mut state = ROOT;
+        let mut matches = vec![];
+        for (i, &b) in haystack.iter().enumerate() {
+            state = self.step(state, b);
+            matches.extend(self.matches_ending_at(state, i + 1));
+        }
+        matches.sort_by_key(|m| (m.start, m.end, m.pattern));
+        matches
+    }
+
+    fn matches_ending_at(
+        &self,
+        state: usize,
+        end: usize,
+    ) -> impl Iterator<Item = PatternMatch> + '_ {
+        self.outputs[state]
+            .iter()
+            .map(move |&pattern| PatternMatch {
+                pattern,
+                start: end - self.lengths[pattern],
+                end,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_all_should_report_overlapping_matches() {
+        let automaton = Automaton::new(&["he", "she", "his", "hers"]);
+        let found: Vec<(usize, usize, usize)> = automaton
+            .find_all(b"ushers")
+            .into_iter()
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect();
+        assert_eq!(found, vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]);
+    }
+
+    #[test]
+    fn find_first_should_prefer_earliest_then_longest() {
+        let automaton = Automaton::new(&["eight", "eighteen", "two", "teen"]);
+        let first = automaton.find_first("xeighteen".bytes()).unwrap();
+        assert_eq!((first.pattern, first.start, first.end), (1, 1, 9));
+        let first = automaton.find_first("eightwo".bytes()).unwrap();
+        assert_eq!((first.pattern, first.start, first.end), (0, 0, 5));
+    }
+
+    #[test]
+    fn find_first_should_handle_no_matches() {
+        let automaton = Automaton::new(&["one", ""]);
+        assert_eq!(automaton.find_first("onx".bytes()), None);
+        assert!(Automaton::new::<&str>(&[]).find_all(b"abc").is_empty());
+    }
+}
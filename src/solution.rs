@@ -0,0 +1,180 @@
+use std::fmt::Display;
+use std::io::BufRead;
+
+use crate::error::{Error, ParseError};
+
+/// Anything that can be printed as a puzzle answer.
+pub type Answer = Box<dyn Display>;
+
+pub type BoxedSolution = Box<dyn Solution>;
+
+/// What an answer reads when its total does not fit in the type it is counted in.
+pub const TOO_LARGE: &str = "too large to count";
+
+/// Answers with a total worked out by checked arithmetic, which is `None` if it
+/// overflowed. This is better than a wrong, wrapped-around number.
+pub fn checked_answer<T: Display + 'static>(total: Option<T>) -> Answer {
+    match total {
+        Some(total) => Box::new(total),
+        None => Box::new(TOO_LARGE),
+    }
+}
+
+/// A single day's puzzle. The input is parsed once, and both parts are answered
+/// from the parsed form.
+pub trait Solution {
+    /// Parses the input one line at a time, so it can come from anywhere without
+    /// being collected first.
+    fn parse_lines<I, S>(lines: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        Self: Sized;
+
+    fn parse(input: &str) -> Result<Self, ParseError>
+    where
+        Self: Sized,
+    {
+        Self::parse_lines(input.lines())
+    }
+
+    fn parse_reader<R: BufRead>(reader: R) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        // stop at the first read error, and report it rather than whatever the
+        // parser made of the truncated input
+        let mut read_error = None;
+        let lines = reader
+            .lines()
+            .map_while(|line| line.map_err(|e| read_error = Some(e)).ok());
+        let parsed = Self::parse_lines(lines);
+        match read_error {
+            Some(e) => Err(Error::Io(e)),
+            None => Ok(parsed?),
+        }
+    }
+
+    fn part1(&self) -> Answer;
+
+    fn part2(&self) -> Answer;
+}
+
+/// An example input from a puzzle description, with the answers it gives.
+pub struct Example {
+    pub name: &'static str,
+    pub input: &'static str,
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+}
+
+impl Example {
+    pub fn expected(&self, part: u8) -> Option<&'static str> {
+        match part {
+            1 => self.part1,
+            _ => self.part2,
+        }
+    }
+}
+
+/// An entry in the registry of implemented days.
+pub struct Day {
+    pub day: u8,
+    pub parse: fn(&str) -> Result<BoxedSolution, ParseError>,
+    pub read: fn(&mut dyn BufRead) -> Result<BoxedSolution, Error>,
+    pub examples: &'static [Example],
+}
+
+impl Day {
+    pub const fn new<S: Solution + 'static>(day: u8, examples: &'static [Example]) -> Day {
+        Day {
+            day,
+            parse: parse_boxed::<S>,
+            read: read_boxed::<S>,
+            examples,
+        }
+    }
+
+    /// Runs `part` on every example that records an answer for it, describing each
+    /// mismatch.
+    pub fn check_examples(&self, part: u8) -> Result<(), String> {
+        let mut failures = vec![];
+        for example in self.examples {
+            let Some(expected) = example.expected(part) else {
+                continue;
+            };
+            let solution = match (self.parse)(example.input) {
+                Ok(solution) => solution,
+                Err(e) => {
+                    failures.push(format!("day {} example {}: {}", self.day, example.name, e));
+                    continue;
+                }
+            };
+            let actual = match part {
+                1 => solution.part1(),
+                _ => solution.part2(),
+            }
+            .to_string();
+            if actual != expected {
+                failures.push(format!(
+                    "day {} example {} part {}: expected {}, got {}",
+                    self.day, example.name, part, expected, actual
+                ));
+            }
+        }
+        match failures.is_empty() {
+            true => Ok(()),
+            false => Err(failures.join("\n")),
+        }
+    }
+}
+
+fn parse_boxed<S: Solution + 'static>(input: &str) -> Result<BoxedSolution, ParseError> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+fn read_boxed<S: Solution + 'static>(reader: &mut dyn BufRead) -> Result<BoxedSolution, Error> {
+    Ok(Box::new(S::parse_reader(reader)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::day_3::Engine;
+
+    #[test]
+    fn check_examples_should_describe_mismatches() {
+        const WRONG: &[Example] = &[Example {
+            name: "wrong",
+            input: "1*1",
+            part1: Some("0"),
+            part2: None,
+        }];
+        let day = Day::new::<Engine>(3, WRONG);
+        assert_eq!(
+            day.check_examples(1),
+            Err("day 3 example wrong part 1: expected 0, got 2".to_string())
+        );
+        assert_eq!(day.check_examples(2), Ok(()));
+    }
+
+    #[test]
+    fn checked_answer_should_say_when_a_total_overflowed() {
+        assert_eq!(checked_answer(Some(12u32)).to_string(), "12");
+        assert_eq!(checked_answer(None::<u32>).to_string(), TOO_LARGE);
+    }
+
+    #[test]
+    fn read_should_parse_from_a_reader() {
+        let mut reader = "467..114..\n...*......\n..35..633.".as_bytes();
+        let solution = (crate::days::get(3).unwrap().read)(&mut reader).unwrap();
+        assert_eq!(solution.part1().to_string(), "502");
+    }
+
+    #[test]
+    fn parse_reader_should_report_read_errors() {
+        // a line that is not valid UTF-8 makes the reader fail part way through
+        let reader = &b"1.\n\xff*\n"[..];
+        assert!(matches!(Engine::parse_reader(reader), Err(Error::Io(_))));
+    }
+}
@@ -1,16 +1,60 @@
-use std::{fs, io};
-pub fn read_file_by_line(file_path: &str) -> Result<Vec<String>, io::Error> {
-    println!("Reading file: {}", file_path);
-    let contents = fs::read_to_string(file_path);
+use crate::error::ParseError;
 
-    match contents {
-        Ok(contents) => {
-            let lines = contents.lines().map(|s| s.to_string()).collect();
-            Ok(lines)
-        }
-        Err(e) => {
-            println!("Error reading file: {}", e);
-            Err(e)
+/// Parses each line with `parse`, tagging any error with the line it came from.
+pub fn parse_lines<I, S, T, F>(lines: I, parse: F) -> Result<Vec<T>, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.as_ref();
+            parse(line).map_err(|e| e.on_line(i + 1, line))
+        })
+        .collect()
+}
+
+/// Quotes `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
     }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_lines_should_report_line_number() {
+        let err = parse_lines(["1", "2", "x"], |s| {
+            s.parse::<u32>()
+                .map_err(|_| ParseError::new(s, s, "a number"))
+        })
+        .unwrap_err();
+        assert_eq!(err.line, Some(3));
+        assert_eq!(
+            err.to_string(),
+            "line 3, column 1: expected a number, found \"x\"\n  x\n  ^"
+        );
+    }
+
+    #[test]
+    fn json_string_should_escape() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), r#""a\"b\\c\n\u0001""#);
+    }
 }